@@ -12,11 +12,156 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 
-criterion_group!(benches, decimal);
+criterion_group!(
+    benches,
+    decimal,
+    bytes,
+    integers,
+    seq_framing,
+    strings,
+    compare,
+    pool
+);
 criterion_main!(benches);
 
+fn integers(c: &mut Criterion) {
+    c.bench_function("serialize_tuple", |b| {
+        let value = (0x1234_5678u32, -42i64, true);
+        let mut buf = Vec::with_capacity(64);
+        b.iter(|| {
+            buf.clear();
+            let mut ser = memcomparable::Serializer::new(&mut buf);
+            serde::Serialize::serialize(&value, &mut ser).unwrap();
+        })
+    });
+
+    let mut group = c.benchmark_group("serialize_vec_u64");
+    for size in [10, 100, 1000] {
+        let v: Vec<u64> = (0..size).collect();
+        let mut buf = Vec::with_capacity(size as usize * 9 + 1);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &v, |b, v| {
+            b.iter(|| {
+                buf.clear();
+                let mut ser = memcomparable::Serializer::new(&mut buf);
+                serde::Serialize::serialize(v, &mut ser).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+fn seq_framing(c: &mut Criterion) {
+    use memcomparable::{Config, Deserializer, SeqFraming, Serializer};
+
+    // a length prefix lets the `Vec` be allocated once instead of growing
+    let mut group = c.benchmark_group("deserialize_vec_u64");
+    let v: Vec<u64> = (0..10_000).collect();
+    for seq_framing in [SeqFraming::Marker, SeqFraming::LengthPrefixed] {
+        let config = Config::new().seq_framing(seq_framing);
+        let mut ser = Serializer::with_config(vec![], config);
+        serde::Serialize::serialize(&v, &mut ser).unwrap();
+        let bytes = ser.into_inner();
+        group.bench_function(format!("{seq_framing:?}"), |b| {
+            b.iter(|| {
+                let mut de = Deserializer::with_config(bytes.as_slice(), config);
+                <Vec<u64> as serde::Deserialize>::deserialize(&mut de).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn compare(c: &mut Criterion) {
+    let a = (42u32, "a user name", -1i64);
+    let b = (42u32, "a user name", 1i64);
+    let mut group = c.benchmark_group("compare");
+    group.bench_function("to_vec", |bench| {
+        bench.iter(|| {
+            memcomparable::to_vec(&a)
+                .unwrap()
+                .cmp(&memcomparable::to_vec(&b).unwrap())
+        })
+    });
+    group.bench_function("compare_by_encoding", |bench| {
+        bench.iter(|| memcomparable::compare_by_encoding(&a, &b).unwrap())
+    });
+    group.finish();
+}
+
+fn pool(c: &mut Criterion) {
+    let value = (42u32, "a user name", -1i64);
+    let pool = memcomparable::SerializerPool::new();
+    let mut group = c.benchmark_group("pool");
+    group.bench_function("to_vec", |b| {
+        b.iter(|| memcomparable::to_vec(&value).unwrap().len())
+    });
+    group.bench_function("pooled", |b| {
+        b.iter(|| {
+            let mut ser = pool.get();
+            serde::Serialize::serialize(&value, &mut *ser).unwrap();
+            ser.bytes().len()
+        })
+    });
+    group.finish();
+}
+
+fn bytes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize_bytes");
+    for size in [10, 100, 1000] {
+        let s = "x".repeat(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &s, |b, s| {
+            b.iter(|| memcomparable::to_vec(s).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn strings(c: &mut Criterion) {
+    use memcomparable::Deserializer;
+    use serde::Deserialize;
+
+    type Row = (String, String, String, String, String);
+
+    let row: Row = (
+        "a user name".into(),
+        "user@example.com".into(),
+        "a somewhat longer description field".into(),
+        "us-east-1".into(),
+        "2024-01-01T00:00:00Z".into(),
+    );
+    let bytes = memcomparable::to_vec(&row).unwrap();
+
+    let mut group = c.benchmark_group("deserialize_strings");
+    group.bench_function("fresh", |b| {
+        b.iter(|| Row::deserialize(&mut Deserializer::new(bytes.as_slice())).unwrap())
+    });
+    group.bench_function("scratch", |b| {
+        let mut scratch = vec![];
+        b.iter(|| {
+            let mut de = Deserializer::with_scratch(bytes.as_slice(), std::mem::take(&mut scratch));
+            let row = Row::deserialize(&mut de).unwrap();
+            scratch = de.take_scratch();
+            row
+        })
+    });
+    group.finish();
+
+    type Record = (u32, Vec<u64>, String);
+    let record: Record = (42, (0..100).collect(), "a user name".into());
+    let bytes = memcomparable::to_vec(&record).unwrap();
+    let mut group = c.benchmark_group("deserialize_record");
+    group.bench_function("from_slice", |b| {
+        b.iter(|| memcomparable::from_slice::<Record>(&bytes).unwrap())
+    });
+    group.bench_function("from_slice_into", |b| {
+        let mut target = Record::default();
+        b.iter(|| memcomparable::from_slice_into(&bytes, &mut target).unwrap())
+    });
+    group.finish();
+}
+
 #[cfg(not(feature = "decimal"))]
 fn decimal(_c: &mut Criterion) {}
 
@@ -0,0 +1,110 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! (De)serialize a `Vec<u8>` or byte slice with the compact `bytes` encoding.
+//!
+//! Serde treats `Vec<u8>` as a sequence, which takes 2 bytes per element plus 1. Use this module
+//! with `#[serde(with = "memcomparable::as_bytes")]` on a field to encode it as a byte array
+//! instead, which takes 9 bytes per 8 plus 1, and orders the same way. This is the same as
+//! `serde_bytes`, without the dependency.
+//!
+//! # Example
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Blob {
+//!     #[serde(with = "memcomparable::as_bytes")]
+//!     data: Vec<u8>,
+//! }
+//!
+//! let blob = Blob { data: vec![0; 1000] };
+//! let bytes = memcomparable::to_vec(&blob).unwrap();
+//! assert_eq!(bytes.len(), 1 + 125 * 9);
+//! assert_eq!(memcomparable::from_slice::<Blob>(&bytes).unwrap(), blob);
+//! ```
+
+use std::fmt;
+
+use serde::de::{self, Visitor};
+use serde::ser;
+
+/// Serialize a byte slice with `serialize_bytes`.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + AsRef<[u8]>,
+    S: ser::Serializer,
+{
+    serializer.serialize_bytes(value.as_ref())
+}
+
+/// Deserialize a byte array serialized by [`serialize`].
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserializer.deserialize_byte_buf(BytesVisitor)
+}
+
+struct BytesVisitor;
+
+impl Visitor<'_> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a byte array")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+    struct Blob(#[serde(with = "super")] Vec<u8>);
+
+    #[test]
+    fn test_as_bytes() {
+        let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let compact = crate::to_vec(&Blob(data.clone())).unwrap();
+        let plain = crate::to_vec(&data).unwrap();
+        assert_eq!(compact.len(), 1126);
+        assert_eq!(plain.len(), 2001);
+        assert_eq!(crate::from_slice::<Blob>(&compact), Ok(Blob(data)));
+        assert_eq!(crate::to_vec(&Blob(vec![])).unwrap(), [0]);
+
+        let mut rng = rand::thread_rng();
+        let mut rand_blob = || {
+            let len = rng.gen_range(0..20);
+            Blob((0..len).map(|_| rng.gen_range(0..3)).collect())
+        };
+        for _ in 0..1000 {
+            let (a, b) = (rand_blob(), rand_blob());
+            let (ea, eb) = (crate::to_vec(&a).unwrap(), crate::to_vec(&b).unwrap());
+            assert_eq!(a.cmp(&b), ea.cmp(&eb), "{a:?} {b:?}");
+            // the plain encoding orders the same way
+            let (pa, pb) = (crate::to_vec(&a.0).unwrap(), crate::to_vec(&b.0).unwrap());
+            assert_eq!(a.cmp(&b), pa.cmp(&pb));
+        }
+    }
+}
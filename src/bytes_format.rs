@@ -0,0 +1,48 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The layout of byte arrays and strings.
+//!
+//! A byte array starts with a marker byte: `0` if it is empty, in which case nothing follows,
+//! or `1` otherwise. A non-empty array is then split into groups of [`CHUNK_SIZE`] bytes, the
+//! last one padded with zeros. Each group is followed by one byte, making a unit of
+//! [`CHUNK_UNIT_SIZE`] bytes:
+//!
+//! - `1..=8`: this is the last group, and this many bytes of it are significant.
+//! - `9`: all bytes of this group are significant, and more groups follow.
+//!
+//! In reverse order, every byte including the markers is flipped.
+//!
+//! # Example
+//! ```
+//! // 3 bytes fit in a single group
+//! assert_eq!(
+//!     memcomparable::to_vec(&"abc").unwrap(),
+//!     [1, b'a', b'b', b'c', 0, 0, 0, 0, 0, 3]
+//! );
+//!
+//! // 10 bytes take a full group and a group with 2 significant bytes
+//! assert_eq!(
+//!     memcomparable::to_vec(&"0123456789").unwrap(),
+//!     [
+//!         1, b'0', b'1', b'2', b'3', b'4', b'5', b'6', b'7', 9, b'8', b'9', 0, 0, 0, 0, 0, 0, 2
+//!     ]
+//! );
+//! ```
+
+/// The number of bytes in each group of a byte array.
+pub const CHUNK_SIZE: usize = 8;
+
+/// The number of bytes in each group of a byte array, including the byte following it.
+pub const CHUNK_UNIT_SIZE: usize = CHUNK_SIZE + 1;
@@ -0,0 +1,135 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::float::NanOrder;
+
+/// Options of the encoding.
+///
+/// Data must be deserialized with the same `Config` it was serialized with, so the same value
+/// should be passed to both [`Serializer::with_config`] and [`Deserializer::with_config`].
+///
+/// [`Serializer::with_config`]: crate::Serializer::with_config
+/// [`Deserializer::with_config`]: crate::Deserializer::with_config
+///
+/// # Example
+/// ```
+/// use memcomparable::{Config, Deserializer, Serializer};
+/// use serde::{Deserialize, Serialize};
+///
+/// let config = Config::new().reverse(true);
+///
+/// let mut ser = Serializer::with_config(vec![], config);
+/// 1u8.serialize(&mut ser).unwrap();
+/// let bytes = ser.into_inner();
+/// assert_eq!(bytes, [0xfe]);
+///
+/// let mut de = Deserializer::with_config(bytes.as_slice(), config);
+/// assert_eq!(u8::deserialize(&mut de).unwrap(), 1);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub(crate) reverse: bool,
+    pub(crate) human_readable: bool,
+    pub(crate) nan_order: NanOrder,
+    pub(crate) seq_framing: SeqFraming,
+    pub(crate) named_variants: bool,
+}
+
+impl Config {
+    /// Create a `Config` with all options set to default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether data is serialized in reverse order. Default is `false`.
+    ///
+    /// If set, all bits will be flipped in serialization.
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Set the value returned by `is_human_readable`. Default is `false`.
+    pub fn human_readable(mut self, human_readable: bool) -> Self {
+        self.human_readable = human_readable;
+        self
+    }
+
+    /// Set where NaN sorts relative to other floats. Default is [`NanOrder::High`].
+    pub fn nan_order(mut self, nan_order: NanOrder) -> Self {
+        self.nan_order = nan_order;
+        self
+    }
+
+    /// Set how sequences are framed. Default is [`SeqFraming::Marker`].
+    pub fn seq_framing(mut self, seq_framing: SeqFraming) -> Self {
+        self.seq_framing = seq_framing;
+        self
+    }
+
+    /// Set whether enum variants are encoded by name instead of index. Default is `false`.
+    ///
+    /// See [`Serializer::set_named_variants`](crate::Serializer::set_named_variants).
+    pub fn named_variants(mut self, named_variants: bool) -> Self {
+        self.named_variants = named_variants;
+        self
+    }
+}
+
+/// How the elements of a sequence are framed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SeqFraming {
+    /// Each element is preceded by a `1`, and the sequence is terminated by a `0`.
+    ///
+    /// Sequences compare lexicographically, element by element.
+    #[default]
+    Marker,
+    /// The number of elements is written first as a `u32`, and the elements follow without
+    /// framing. This takes 4 bytes per sequence instead of 1 per element plus 1. The length is
+    /// also passed to serde as an exact size hint, so a `Vec` is allocated once on decoding.
+    ///
+    /// **Sequences of different lengths do not preserve the order**: a shorter sequence
+    /// always sorts first, e.g. `[2]` before `[1, 1]`.
+    LengthPrefixed,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{Deserializer, Serializer};
+
+    #[test]
+    fn test_shared_config() {
+        let config = Config::new().reverse(true).human_readable(true);
+        let value = (Ipv4Addr::new(10, 0, 0, 1), -3i32, "key".to_string());
+
+        let mut ser = Serializer::with_config(vec![], config);
+        value.serialize(&mut ser).unwrap();
+        let bytes = ser.into_inner();
+
+        // the address is encoded as a string, with all bits flipped
+        let mut expected = Serializer::new(vec![]);
+        ("10.0.0.1", -3i32, "key").serialize(&mut expected).unwrap();
+        let expected: Vec<u8> = expected.into_inner().iter().map(|b| !b).collect();
+        assert_eq!(bytes, expected);
+
+        let mut de = Deserializer::with_config(bytes.as_slice(), config);
+        assert_eq!(<_>::deserialize(&mut de), Ok(value));
+        assert!(!de.has_remaining());
+    }
+}
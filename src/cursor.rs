@@ -0,0 +1,168 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::Buf;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::{Deserializer, Input, Result};
+
+/// A cursor decoding the fields of a composite key one at a time.
+///
+/// Each `next_*` method decodes one field and advances past it, so a decoder can pull fields on
+/// demand and stop early, leaving the rest of the input unread.
+///
+/// # Example
+/// ```
+/// use memcomparable::FieldCursor;
+///
+/// let key = memcomparable::to_vec(&(7u32, "name", -1i64)).unwrap();
+/// let mut cursor = FieldCursor::new(key.as_slice());
+/// assert_eq!(cursor.next_u32().unwrap(), 7);
+/// assert_eq!(cursor.next_string().unwrap(), "name");
+/// assert_eq!(cursor.next_i64().unwrap(), -1);
+/// assert!(!cursor.has_remaining());
+/// ```
+#[derive(Clone)]
+pub struct FieldCursor<B: Buf> {
+    de: Deserializer<B>,
+}
+
+macro_rules! next_method {
+    ($($name:ident: $ty:ty),*) => {
+        $(
+            #[doc = concat!("Decode the next field as a `", stringify!($ty), "`.")]
+            pub fn $name<'de>(&mut self) -> Result<$ty>
+            where
+                B: Input<'de>,
+            {
+                <$ty>::deserialize(&mut self.de)
+            }
+        )*
+    };
+}
+
+impl<B: Buf> FieldCursor<B> {
+    /// Create a cursor at the start of `input`.
+    pub fn new(input: B) -> Self {
+        Self::from_deserializer(Deserializer::new(input))
+    }
+
+    /// Create a cursor decoding with a configured deserializer, e.g. for reverse order.
+    pub fn from_deserializer(de: Deserializer<B>) -> Self {
+        FieldCursor { de }
+    }
+
+    /// Unwrap the deserializer, positioned after the fields decoded so far.
+    pub fn into_inner(self) -> Deserializer<B> {
+        self.de
+    }
+
+    /// Decode the next field as any deserializable type.
+    pub fn next_value<'de, T: DeserializeOwned>(&mut self) -> Result<T>
+    where
+        B: Input<'de>,
+    {
+        T::deserialize(&mut self.de)
+    }
+
+    next_method! {
+        next_bool: bool,
+        next_u8: u8,
+        next_u16: u16,
+        next_u32: u32,
+        next_u64: u64,
+        next_u128: u128,
+        next_i8: i8,
+        next_i16: i16,
+        next_i32: i32,
+        next_i64: i64,
+        next_i128: i128,
+        next_f32: f32,
+        next_f64: f64,
+        next_char: char,
+        next_string: String
+    }
+
+    /// Decode the next field as a byte array.
+    pub fn next_bytes(&mut self) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        self.de.read_bytes_into(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Decode the next field as a decimal.
+    #[cfg(feature = "decimal")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+    pub fn next_decimal(&mut self) -> Result<crate::Decimal> {
+        self.de.deserialize_decimal()
+    }
+
+    /// Skip the next field, which is a byte array or string.
+    pub fn skip_bytes(&mut self) -> Result<()> {
+        self.de.skip_bytes().map(|_| ())
+    }
+
+    /// Return the number of bytes decoded so far.
+    pub fn position(&self) -> usize {
+        self.de.position()
+    }
+
+    /// Return whether there are fields left to decode.
+    pub fn has_remaining(&self) -> bool {
+        self.de.has_remaining()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn test_field_cursor() {
+        let key = crate::to_vec(&(42u32, "a string", -7i64)).unwrap();
+        let mut cursor = FieldCursor::new(key.as_slice());
+        assert_eq!(cursor.next_u32(), Ok(42));
+        assert_eq!(cursor.position(), 4);
+        assert_eq!(cursor.next_string().unwrap(), "a string");
+        assert_eq!(cursor.next_i64(), Ok(-7));
+        assert!(!cursor.has_remaining());
+        assert_eq!(cursor.next_u8().map_err(Error::into_inner), Err(Error::Eof));
+
+        // stopping early leaves the rest of the key unread
+        let mut cursor = FieldCursor::new(key.as_slice());
+        assert_eq!(cursor.next_u32(), Ok(42));
+        cursor.skip_bytes().unwrap();
+        assert_eq!(cursor.position(), 4 + 10);
+        let de = cursor.into_inner();
+        assert_eq!(de.remaining(), 8);
+
+        let key = crate::to_vec(&(true, 'x', 1.5f64, (1u8, 2u16), b"ab".as_slice())).unwrap();
+        let mut cursor = FieldCursor::new(key.as_slice());
+        assert_eq!(cursor.next_bool(), Ok(true));
+        assert_eq!(cursor.next_char(), Ok('x'));
+        assert_eq!(cursor.next_f64(), Ok(1.5));
+        assert_eq!(cursor.next_value::<(u8, u16)>(), Ok((1, 2)));
+        // a `&[u8]` is a sequence of `u8` to serde
+        assert_eq!(cursor.next_value::<Vec<u8>>().unwrap(), b"ab");
+
+        let key = crate::to_vec_desc(&(1u8, "z")).unwrap();
+        let mut de = Deserializer::new(key.as_slice());
+        de.set_reverse(true);
+        let mut cursor = FieldCursor::from_deserializer(de);
+        assert_eq!(cursor.next_u8(), Ok(1));
+        assert_eq!(cursor.next_string().unwrap(), "z");
+    }
+}
@@ -12,22 +12,43 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use bytes::Buf;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use bytes::{Buf, Bytes};
 use serde::de::{
-    self, DeserializeSeed, EnumAccess, IntoDeserializer, SeqAccess, VariantAccess, Visitor,
+    self, Deserialize, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, SeqAccess,
+    VariantAccess, Visitor,
 };
 
+use crate::bytes_format::{CHUNK_SIZE, CHUNK_UNIT_SIZE};
+use crate::config::{Config, SeqFraming};
+use crate::crc::crc32;
 #[cfg(feature = "decimal")]
-use crate::decimal::Decimal;
+use crate::decimal::{
+    Decimal, FINITE_DECIMAL_NEG_BASE, FINITE_DECIMAL_POS_BASE, FINITE_DECIMAL_ZERO,
+};
+use crate::dynamic::DynValue;
 use crate::error::{Error, Result};
 
-const BYTES_CHUNK_SIZE: usize = 8;
-const BYTES_CHUNK_UNIT_SIZE: usize = BYTES_CHUNK_SIZE + 1;
+const DEFAULT_MAX_DEPTH: usize = 128;
 
 /// A structure that deserializes memcomparable bytes into Rust values.
+#[derive(Clone)]
 pub struct Deserializer<B: Buf> {
     input: MaybeFlip<B>,
     input_len: usize,
+    human_readable: bool,
+    strict: bool,
+    max_alloc: usize,
+    depth: usize,
+    max_depth: usize,
+    seq_framing: SeqFraming,
+    named_variants: bool,
+    // buffer reused for decoding multi-chunk byte arrays and strings, if enabled
+    scratch: Option<Vec<u8>>,
 }
 
 impl<B: Buf> Deserializer<B> {
@@ -36,9 +57,60 @@ impl<B: Buf> Deserializer<B> {
         Deserializer {
             input_len: input.remaining(),
             input: MaybeFlip { input, flip: false },
+            human_readable: false,
+            strict: false,
+            max_alloc: usize::MAX,
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            seq_framing: SeqFraming::Marker,
+            named_variants: false,
+            scratch: None,
         }
     }
 
+    /// Creates a deserializer from a buffer with the given [`Config`].
+    pub fn with_config(input: B, config: Config) -> Self {
+        let mut deserializer = Self::new(input);
+        deserializer.set_reverse(config.reverse);
+        deserializer.set_human_readable(config.human_readable);
+        deserializer.set_seq_framing(config.seq_framing);
+        deserializer.set_named_variants(config.named_variants);
+        deserializer
+    }
+
+    /// Creates a deserializer from a buffer that decodes strings and bytes into `scratch`.
+    ///
+    /// Instead of allocating a new `Vec` for each field, the data is decoded into `scratch` and
+    /// visited as a borrowed `&str` or `&[u8]`, so types like `String` copy it once into an
+    /// exactly sized allocation. The buffer can be taken back with
+    /// [`take_scratch`](Self::take_scratch) and reused for the next deserializer.
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// let mut scratch = vec![];
+    /// for name in ["first name", "second name"] {
+    ///     let key = memcomparable::to_vec(&name).unwrap();
+    ///     let mut de = Deserializer::with_scratch(key.as_slice(), scratch);
+    ///     assert_eq!(String::deserialize(&mut de).unwrap(), name);
+    ///     scratch = de.take_scratch();
+    /// }
+    /// ```
+    pub fn with_scratch(input: B, scratch: Vec<u8>) -> Self {
+        let mut deserializer = Self::new(input);
+        deserializer.scratch = Some(scratch);
+        deserializer
+    }
+
+    /// Take the scratch buffer given to [`with_scratch`](Self::with_scratch).
+    ///
+    /// Returns an empty `Vec` if there is none. Later fields are decoded without it.
+    pub fn take_scratch(&mut self) -> Vec<u8> {
+        self.scratch.take().unwrap_or_default()
+    }
+
     /// Set whether data is serialized in reverse order.
     ///
     /// If set, all bits will be flipped in serialization.
@@ -46,6 +118,52 @@ impl<B: Buf> Deserializer<B> {
         self.input.flip = reverse;
     }
 
+    /// Set the value returned by `is_human_readable`. Default is `false`.
+    ///
+    /// It must match the setting of the `Serializer` that produced the data.
+    pub fn set_human_readable(&mut self, human_readable: bool) {
+        self.human_readable = human_readable;
+    }
+
+    /// Set how sequences are framed. Default is [`SeqFraming::Marker`].
+    ///
+    /// It must match the setting of the `Serializer` that produced the data.
+    pub fn set_seq_framing(&mut self, seq_framing: SeqFraming) {
+        self.seq_framing = seq_framing;
+    }
+
+    /// Set whether enum variants are encoded by name instead of index. Default is `false`.
+    ///
+    /// It must match the setting of the `Serializer` that produced the data.
+    pub fn set_named_variants(&mut self, named_variants: bool) {
+        self.named_variants = named_variants;
+    }
+
+    /// Set whether to reject encodings that `Serializer` would not produce. Default is `false`.
+    ///
+    /// In strict mode, decimal significands with trailing zeros are rejected with
+    /// [`Error::NonCanonical`], so that each accepted value has exactly one encoding.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Set the maximum length of a decoded byte array or string. Default is unbounded.
+    ///
+    /// Longer ones are rejected with [`Error::LengthLimitExceeded`] before they are fully
+    /// buffered. Set this when deserializing untrusted input.
+    pub fn set_max_alloc(&mut self, max_alloc: usize) {
+        self.max_alloc = max_alloc;
+    }
+
+    /// Set the maximum nesting depth of options, sequences, tuples, structs and enums.
+    /// Default is 128.
+    ///
+    /// Deeper values are rejected with [`Error::DepthLimitExceeded`], so that a malicious input
+    /// for a recursive type can not overflow the stack.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
     /// Unwrap the inner buffer from the `Deserializer`.
     pub fn into_inner(self) -> B {
         self.input.input
@@ -56,17 +174,156 @@ impl<B: Buf> Deserializer<B> {
         self.input.input.has_remaining()
     }
 
+    /// Return the number of bytes left in the inner buffer.
+    pub fn remaining(&self) -> usize {
+        self.input.input.remaining()
+    }
+
     /// Return the position of inner buffer from the `Deserializer`.
     pub fn position(&self) -> usize {
         self.input_len - self.input.input.remaining()
     }
 
+    /// Check that the whole input has been consumed after decoding a value.
+    pub(crate) fn end(&self) -> Result<()> {
+        if self.has_remaining() {
+            let remaining = self.remaining();
+            return Err(Error::TrailingCharacters { remaining }.at(self.position()));
+        }
+        Ok(())
+    }
+
     /// Advance the position of inner buffer from the `Deserializer`.
+    ///
+    /// After an error, the position is somewhere inside the value that failed. If the caller
+    /// knows where the next value starts, it can resync by advancing to it. See also
+    /// [`decode_batch_lossy`] for records of a fixed length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cnt` is greater than [`remaining`](Self::remaining).
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::Deserializer;
+    /// use serde::Deserialize;
+    ///
+    /// // three 2-byte records, the second of which is not a valid bool pair
+    /// let bytes = [0, 1, 0, 2, 1, 1];
+    /// let mut de = Deserializer::new(&bytes[..]);
+    /// let mut values = vec![];
+    /// while de.has_remaining() {
+    ///     let start = de.position();
+    ///     match <(bool, bool)>::deserialize(&mut de) {
+    ///         Ok(v) => values.push(v),
+    ///         Err(_) => de.advance(start + 2 - de.position()),
+    ///     }
+    /// }
+    /// assert_eq!(values, [(false, true), (true, true)]);
+    /// ```
     pub fn advance(&mut self, cnt: usize) {
         self.input.input.advance(cnt)
     }
 }
 
+impl Deserializer<Bytes> {
+    /// Creates a deserializer owning a `Vec<u8>`, without copying it.
+    pub fn from_vec(input: Vec<u8>) -> Self {
+        Self::new(Bytes::from(input))
+    }
+
+    /// Creates a deserializer owning a `Bytes`.
+    pub fn from_bytes(input: Bytes) -> Self {
+        Self::new(input)
+    }
+}
+
+impl<'a> Deserializer<&'a [u8]> {
+    /// Skip the leading fields of a key and return the bytes they occupy.
+    ///
+    /// This is useful to make a bound for a range scan over a prefix of a composite key.
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::{Deserializer, Skip};
+    ///
+    /// let key = memcomparable::to_vec(&(1u32, "a", -1i64)).unwrap();
+    /// let mut de = Deserializer::new(key.as_slice());
+    /// let prefix = de.extract_prefix(&[Skip::Fixed(4), Skip::Bytes]).unwrap();
+    /// assert_eq!(prefix, memcomparable::to_vec(&(1u32, "a")).unwrap());
+    /// ```
+    pub fn extract_prefix(&mut self, skips: &[Skip]) -> Result<&'a [u8]> {
+        let input = self.input.input;
+        let start = self.position();
+        for &skip in skips {
+            self.skip(skip)?;
+        }
+        Ok(&input[..self.position() - start])
+    }
+
+    /// Skip the next field, decoded as a `T`, and return the successor of all the bytes consumed
+    /// so far.
+    ///
+    /// `key` must be the slice the deserializer was created with, whose prefix is the consumed
+    /// bytes. The result is the exclusive upper bound of the keys sharing the fields read so far,
+    /// see [`successor`](crate::successor). A grouped scan can seek to it to skip the rest of the
+    /// current group. Returns `None` if there is no upper bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not as long as the input of the deserializer.
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::Deserializer;
+    ///
+    /// let key = memcomparable::to_vec(&(1u32, "a", 5u8)).unwrap();
+    /// let mut de = Deserializer::new(key.as_slice());
+    /// let bound = de.advance_past_field::<u32>(&key).unwrap().unwrap();
+    /// assert!(key.as_slice() < bound.as_slice());
+    /// assert!(bound < memcomparable::to_vec(&(2u32, "")).unwrap());
+    /// ```
+    pub fn advance_past_field<T: DeserializeOwned>(
+        &mut self,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>> {
+        assert_eq!(
+            key.len(),
+            self.input_len,
+            "key is not the input of the deserializer"
+        );
+        T::deserialize(&mut *self)?;
+        Ok(crate::successor(&key[..self.position()]))
+    }
+
+    /// Decode the next byte array, borrowing it from the input where possible.
+    ///
+    /// The bytes are borrowed if they are not in reverse order and fit in a single chunk,
+    /// i.e. at most 8 bytes. Otherwise they are copied.
+    ///
+    /// # Example
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use memcomparable::Deserializer;
+    ///
+    /// let key = memcomparable::to_vec(&"short").unwrap();
+    /// let mut de = Deserializer::new(key.as_slice());
+    /// let bytes = de.read_borrowed_bytes().unwrap();
+    /// assert!(matches!(bytes, Cow::Borrowed(b"short")));
+    /// ```
+    pub fn read_borrowed_bytes(&mut self) -> Result<Cow<'a, [u8]>> {
+        let position = self.position();
+        match self.borrow_bytes().map_err(|e| e.at(position))? {
+            Some(bytes) => Ok(Cow::Borrowed(bytes)),
+            None => self
+                .read_bytes()
+                .map(Cow::Owned)
+                .map_err(|e| e.at(position)),
+        }
+    }
+}
+
 /// Deserialize an instance of type `T` from a memcomparable bytes.
 pub fn from_slice<'a, T>(bytes: &'a [u8]) -> Result<T>
 where
@@ -74,14 +331,243 @@ where
 {
     let mut deserializer = Deserializer::new(bytes);
     let t = T::deserialize(&mut deserializer)?;
-    if deserializer.input.is_empty() {
-        Ok(t)
-    } else {
-        Err(Error::TrailingCharacters)
+    deserializer.end()?;
+    Ok(t)
+}
+
+/// Deserialize an instance of type `T` from a memcomparable bytes into an existing value.
+///
+/// This uses serde's in-place deserialization, so `Vec` and `String` in `target` keep their
+/// allocations when decoding many records into a reused value. Tuples, arrays and collections
+/// support it out of the box, while derived structs need the `deserialize_in_place` feature of
+/// `serde_derive`, and are otherwise replaced as a whole.
+///
+/// If an error is returned, `target` may be partially overwritten.
+///
+/// # Example
+/// ```
+/// let mut row: (u32, Vec<u64>, String) = Default::default();
+/// for i in 0..3 {
+///     let bytes = memcomparable::to_vec(&(i, vec![1u64, 2], "name")).unwrap();
+///     memcomparable::from_slice_into(&bytes, &mut row).unwrap();
+///     assert_eq!(row, (i, vec![1, 2], "name".to_string()));
+/// }
+/// ```
+pub fn from_slice_into<T>(bytes: &[u8], target: &mut T) -> Result<()>
+where
+    T: for<'de> serde::Deserialize<'de>,
+{
+    // strings are decoded through the scratch buffer and copied into the existing `String`
+    let mut deserializer = Deserializer::with_scratch(bytes, vec![]);
+    T::deserialize_in_place(&mut deserializer, target)?;
+    deserializer.end()
+}
+
+/// Deserialize an instance of type `T` from a memcomparable bytes with a checksum trailer,
+/// written by [`Serializer::finish_with_checksum`](crate::Serializer::finish_with_checksum).
+///
+/// Returns [`Error::ChecksumMismatch`] if the trailer does not match the body, before anything
+/// is decoded.
+pub fn from_slice_checked<'a, T>(bytes: &'a [u8]) -> Result<T>
+where
+    T: serde::Deserialize<'a>,
+{
+    let Some(split) = bytes.len().checked_sub(4) else {
+        return Err(Error::Eof);
+    };
+    let (body, trailer) = bytes.split_at(split);
+    let expected = u32::from_be_bytes(trailer.try_into().unwrap());
+    let actual = crc32(body);
+    if expected != actual {
+        return Err(Error::ChecksumMismatch { expected, actual });
+    }
+    from_slice(body)
+}
+
+/// Deserialize an instance of type `T` from a memcomparable bytes encoded in descending order.
+///
+/// This is the inverse of [`to_vec_desc`](crate::to_vec_desc).
+pub fn from_slice_desc<'a, T>(bytes: &'a [u8]) -> Result<T>
+where
+    T: serde::Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::new(bytes);
+    deserializer.set_reverse(true);
+    let t = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(t)
+}
+
+/// Deserialize an instance of type `T` from an owned memcomparable bytes.
+///
+/// This accepts a `Vec<u8>` or `Bytes` without borrowing it.
+///
+/// # Example
+/// ```
+/// let key: Vec<u8> = memcomparable::to_vec(&(1u8, "a")).unwrap();
+/// let value: (u8, String) = memcomparable::from_owned(key).unwrap();
+/// assert_eq!(value, (1, "a".to_string()));
+/// ```
+pub fn from_owned<T>(bytes: impl Into<Bytes>) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = Deserializer::from_bytes(bytes.into());
+    let t = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(t)
+}
+
+/// Deserialize an instance of type `T` from the front of a memcomparable bytes.
+///
+/// On success, `bytes` is advanced past the consumed data and left untouched otherwise.
+/// This is useful for decoding several values from one buffer in turn.
+///
+/// # Example
+/// ```
+/// let mut buf = &[0x00, 0x01, 0x01][..];
+/// let a: u16 = memcomparable::from_slice_advancing(&mut buf).unwrap();
+/// let b: bool = memcomparable::from_slice_advancing(&mut buf).unwrap();
+/// assert_eq!((a, b), (1, true));
+/// assert!(buf.is_empty());
+/// ```
+pub fn from_slice_advancing<'a, T>(bytes: &mut &'a [u8]) -> Result<T>
+where
+    T: serde::Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::new(*bytes);
+    let t = T::deserialize(&mut deserializer)?;
+    *bytes = deserializer.into_inner();
+    Ok(t)
+}
+
+/// Deserialize an instance of type `T` from the front of a memcomparable bytes, ignoring the rest.
+///
+/// This is useful to read the leading column of a composite key without decoding the others.
+///
+/// # Example
+/// ```
+/// let key = memcomparable::to_vec(&(7u32, "user", -1i64)).unwrap();
+/// let shard: u32 = memcomparable::peek_first(&key).unwrap();
+/// assert_eq!(shard, 7);
+/// ```
+pub fn peek_first<T>(bytes: &[u8]) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    from_slice_prefix(bytes)
+}
+
+/// Deserialize an instance of type `T` from a memcomparable bytes, ignoring trailing bytes.
+///
+/// Unlike [`from_slice`], this does not return [`Error::TrailingCharacters`], so keys written
+/// with columns appended to their schema can still be read by code that only knows the leading
+/// columns. Columns must only ever be added at the end for this to hold.
+///
+/// # Example
+/// ```
+/// let key = memcomparable::to_vec(&(7u32, "user", true)).unwrap();
+/// let (id, name): (u32, String) = memcomparable::from_slice_prefix(&key).unwrap();
+/// assert_eq!((id, name.as_str()), (7, "user"));
+/// ```
+pub fn from_slice_prefix<'a, T>(bytes: &'a [u8]) -> Result<T>
+where
+    T: serde::Deserialize<'a>,
+{
+    T::deserialize(&mut Deserializer::new(bytes))
+}
+
+/// Deserialize a batch of records of `record_len` bytes each, skipping those that fail.
+///
+/// Each record must decode as a `T` that spans it exactly. Records that do not are reported
+/// along with their index, and decoding resumes at the next record. The error positions are
+/// relative to the start of `bytes`. A shorter record at the end is decoded as well and
+/// usually fails.
+///
+/// # Panics
+///
+/// Panics if `record_len` is 0.
+///
+/// # Example
+/// ```
+/// // the second record is not a valid bool
+/// let (values, errors) = memcomparable::decode_batch_lossy::<bool>(&[1, 2, 0], 1);
+/// assert_eq!(values, [true, false]);
+/// assert_eq!(errors[0].0, 1);
+/// ```
+pub fn decode_batch_lossy<'a, T>(
+    bytes: &'a [u8],
+    record_len: usize,
+) -> (Vec<T>, Vec<(usize, Error)>)
+where
+    T: serde::Deserialize<'a>,
+{
+    assert!(record_len > 0, "record length must be positive");
+    let mut values = vec![];
+    let mut errors = vec![];
+    for (index, record) in bytes.chunks(record_len).enumerate() {
+        let mut deserializer = Deserializer::new(record);
+        let result = T::deserialize(&mut deserializer).and_then(|t| {
+            deserializer.end()?;
+            Ok(t)
+        });
+        match result {
+            Ok(t) => values.push(t),
+            Err(e) => {
+                let offset = index * record_len;
+                let e = match e {
+                    Error::At { position, source } => Error::At {
+                        position: offset + position,
+                        source,
+                    },
+                    e => e.at(offset),
+                };
+                errors.push((index, e));
+            }
+        }
     }
+    (values, errors)
+}
+
+/// Deserialize a stream of concatenated `T` values until `bytes` is exhausted.
+///
+/// The iterator stops after yielding the first error. A value that consumes no input while
+/// bytes remain, such as `()`, is reported as [`Error::TrailingCharacters`] instead of
+/// looping forever.
+///
+/// # Example
+/// ```
+/// let bytes = memcomparable::to_vec(&(1u16, 2u16, 3u16)).unwrap();
+/// let values: Vec<u16> = memcomparable::decode_iter(&bytes)
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(values, [1, 2, 3]);
+/// ```
+pub fn decode_iter<'a, T>(bytes: &'a [u8]) -> impl Iterator<Item = Result<T>> + 'a
+where
+    T: serde::Deserialize<'a> + 'a,
+{
+    let mut deserializer = Deserializer::new(bytes);
+    let mut done = false;
+    std::iter::from_fn(move || {
+        if done || !deserializer.has_remaining() {
+            return None;
+        }
+        let position = deserializer.position();
+        let result = T::deserialize(&mut deserializer);
+        if result.is_err() {
+            done = true;
+        } else if deserializer.position() == position {
+            done = true;
+            let remaining = deserializer.remaining();
+            return Some(Err(Error::TrailingCharacters { remaining }.at(position)));
+        }
+        Some(result)
+    })
 }
 
 /// A wrapper around `Buf` that can flip bits when getting data.
+#[derive(Clone)]
 struct MaybeFlip<B: Buf> {
     input: B,
     flip: bool,
@@ -89,13 +575,12 @@ struct MaybeFlip<B: Buf> {
 
 macro_rules! def_method {
     ($name:ident, $ty:ty) => {
-        fn $name(&mut self) -> $ty {
-            let v = self.input.$name();
-            if self.flip {
-                !v
-            } else {
-                v
+        fn $name(&mut self) -> Result<$ty> {
+            if self.input.remaining() < std::mem::size_of::<$ty>() {
+                return Err(Error::Eof);
             }
+            let v = self.input.$name();
+            Ok(if self.flip { !v } else { v })
         }
     };
 }
@@ -111,11 +596,15 @@ impl<B: Buf> MaybeFlip<B> {
 
     def_method!(get_u128, u128);
 
-    fn copy_to_slice(&mut self, dst: &mut [u8]) {
+    fn copy_to_slice(&mut self, dst: &mut [u8]) -> Result<()> {
+        if self.input.remaining() < dst.len() {
+            return Err(Error::Eof);
+        }
         self.input.copy_to_slice(dst);
         if self.flip {
             dst.iter_mut().for_each(|x| *x = !*x);
         }
+        Ok(())
     }
 
     fn is_empty(&self) -> bool {
@@ -123,51 +612,314 @@ impl<B: Buf> MaybeFlip<B> {
     }
 }
 
+/// An input buffer of a [`Deserializer`].
+///
+/// A slice borrowed for `'de` can lend its bytes to the values being deserialized, so that
+/// `&'de [u8]` fields are decoded without copying. Other buffers only copy. It is implemented for
+/// the buffers of `bytes` and `std`, and any other `Buf` can implement it with the defaults.
+pub trait Input<'de>: Buf {
+    /// Return the remaining bytes if they are borrowed for `'de`.
+    fn borrow_remaining(&self) -> Option<&'de [u8]> {
+        None
+    }
+}
+
+impl<'de, 'a: 'de> Input<'de> for &'a [u8] {
+    fn borrow_remaining(&self) -> Option<&'de [u8]> {
+        Some(self)
+    }
+}
+
+impl Input<'_> for Bytes {}
+
+impl Input<'_> for bytes::BytesMut {}
+
+impl Input<'_> for std::collections::VecDeque<u8> {}
+
+impl<T: AsRef<[u8]>> Input<'_> for std::io::Cursor<T> {}
+
+impl<T: Buf> Input<'_> for bytes::buf::Take<T> {}
+
+impl<T: Buf, U: Buf> Input<'_> for bytes::buf::Chain<T, U> {}
+
+impl<'de, T: Input<'de> + ?Sized> Input<'de> for &mut T {
+    fn borrow_remaining(&self) -> Option<&'de [u8]> {
+        (**self).borrow_remaining()
+    }
+}
+
+impl<'de, T: Input<'de> + ?Sized> Input<'de> for Box<T> {
+    fn borrow_remaining(&self) -> Option<&'de [u8]> {
+        (**self).borrow_remaining()
+    }
+}
+
 impl<B: Buf> Deserializer<B> {
     fn read_bytes(&mut self) -> Result<Vec<u8>> {
-        match self.input.get_u8() {
-            0 => return Ok(vec![]), // empty slice
-            1 => {}                 // non-empty slice
+        let mut bytes = vec![];
+        self.append_bytes(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Decode the next byte array and append it to `out`. Return the number of bytes appended.
+    ///
+    /// Unlike deserializing a `Vec<u8>`, this does not allocate, so a scratch buffer can be
+    /// reused across records by clearing it before each call. On error, `out` is left unchanged.
+    pub fn read_bytes_into(&mut self, out: &mut Vec<u8>) -> Result<usize> {
+        let position = self.position();
+        let len = out.len();
+        self.append_bytes(out).map_err(|e| {
+            out.truncate(len);
+            e.at(position)
+        })?;
+        Ok(out.len() - len)
+    }
+
+    fn append_bytes(&mut self, bytes: &mut Vec<u8>) -> Result<()> {
+        match self.input.get_u8()? {
+            0 => return Ok(()), // empty slice
+            1 => {}             // non-empty slice
             v => return Err(Error::InvalidBytesEncoding(v)),
         }
-        let mut bytes = vec![];
-        let mut chunk = [0u8; BYTES_CHUNK_UNIT_SIZE]; // chunk + chunk_len
+        let start = bytes.len();
+        self.append_chunks(bytes, start)
+    }
+
+    /// Decode the next byte array and pass it to `f`.
+    ///
+    /// A byte array of a single chunk is decoded on the stack without allocation, and a longer
+    /// one into the scratch buffer if there is one.
+    fn with_bytes<R>(&mut self, f: impl FnOnce(&[u8]) -> Result<R>) -> Result<R> {
+        match self.input.get_u8()? {
+            0 => return f(&[]), // empty slice
+            1 => {}             // non-empty slice
+            v => return Err(Error::InvalidBytesEncoding(v)),
+        }
+        let mut chunk = [0u8; CHUNK_UNIT_SIZE]; // chunk + chunk_len
+        self.input.copy_to_slice(&mut chunk)?;
+        match chunk[CHUNK_SIZE] {
+            len @ 1..=8 => {
+                check_padding(&chunk[len as usize..CHUNK_SIZE])?;
+                self.check_len(len as usize)?;
+                f(&chunk[..len as usize])
+            }
+            9 => {
+                let mut bytes = self
+                    .scratch
+                    .as_mut()
+                    .map(std::mem::take)
+                    .unwrap_or_default();
+                bytes.clear();
+                let result = self
+                    .extend_limited(&mut bytes, 0, &chunk[..CHUNK_SIZE])
+                    .and_then(|_| self.append_chunks(&mut bytes, 0))
+                    .and_then(|_| f(&bytes));
+                if let Some(scratch) = &mut self.scratch {
+                    *scratch = bytes;
+                }
+                result
+            }
+            v => Err(Error::InvalidBytesEncoding(v)),
+        }
+    }
+
+    /// Decode the chunks of a non-empty byte array and append them to `bytes`.
+    ///
+    /// The byte array starts at `start` in `bytes`.
+    fn append_chunks(&mut self, bytes: &mut Vec<u8>, start: usize) -> Result<()> {
+        let mut chunk = [0u8; CHUNK_UNIT_SIZE]; // chunk + chunk_len
         loop {
-            self.input.copy_to_slice(&mut chunk);
-            match chunk[8] {
+            self.input.copy_to_slice(&mut chunk)?;
+            match chunk[CHUNK_SIZE] {
                 len @ 1..=8 => {
-                    bytes.extend_from_slice(&chunk[..len as usize]);
-                    return Ok(bytes);
+                    check_padding(&chunk[len as usize..CHUNK_SIZE])?;
+                    return self.extend_limited(bytes, start, &chunk[..len as usize]);
                 }
-                9 => bytes.extend_from_slice(&chunk[..8]),
+                9 => self.extend_limited(bytes, start, &chunk[..CHUNK_SIZE])?,
                 v => return Err(Error::InvalidBytesEncoding(v)),
             }
         }
     }
 
+    /// Append `data` to the byte array starting at `start` in `bytes`, checking `max_alloc`.
+    fn extend_limited(&self, bytes: &mut Vec<u8>, start: usize, data: &[u8]) -> Result<()> {
+        self.check_len(bytes.len() - start + data.len())?;
+        bytes.extend_from_slice(data);
+        Ok(())
+    }
+
+    /// Run `f` one level deeper in the nesting, checking `max_depth`.
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth >= self.max_depth {
+            return Err(Error::DepthLimitExceeded {
+                limit: self.max_depth,
+            });
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Check the length of a byte array against `max_alloc`.
+    fn check_len(&self, len: usize) -> Result<()> {
+        if len > self.max_alloc {
+            return Err(Error::LengthLimitExceeded {
+                limit: self.max_alloc,
+            });
+        }
+        Ok(())
+    }
+
     /// Skip the next byte array. Return the length of it.
     pub fn skip_bytes(&mut self) -> Result<usize> {
-        match self.input.get_u8() {
+        match self.input.get_u8()? {
             0 => return Ok(0), // empty slice
             1 => {}            // non-empty slice
             v => return Err(Error::InvalidBytesEncoding(v)),
         }
         let mut total_len = 0;
+        let mut chunk = [0u8; CHUNK_UNIT_SIZE]; // chunk + chunk_len
         loop {
-            self.advance(BYTES_CHUNK_SIZE);
-            match self.input.get_u8() {
-                len @ 1..=8 => return Ok(total_len + len as usize),
+            // the content is still read to check the padding
+            self.input.copy_to_slice(&mut chunk)?;
+            match chunk[CHUNK_SIZE] {
+                len @ 1..=8 => {
+                    check_padding(&chunk[len as usize..CHUNK_SIZE])?;
+                    return Ok(total_len + len as usize);
+                }
                 9 => total_len += 8,
                 v => return Err(Error::InvalidBytesEncoding(v)),
             }
         }
     }
+
+    /// Skip the next field with the given encoding.
+    pub fn skip(&mut self, skip: Skip) -> Result<()> {
+        let position = self.position();
+        match skip {
+            Skip::Fixed(len) => {
+                if self.remaining() < len {
+                    return Err(Error::Eof.at(position));
+                }
+                self.input.input.advance(len);
+                Ok(())
+            }
+            Skip::Bytes => self.skip_bytes().map(|_| ()).map_err(|e| e.at(position)),
+            #[cfg(feature = "decimal")]
+            Skip::Decimal => self.deserialize_decimal().map(|_| ()),
+        }
+    }
+}
+
+impl<'de, B: Input<'de>> Deserializer<B> {
+    /// Decode the next byte array by borrowing it from the input, if it is not in reverse order
+    /// and fits in a single chunk.
+    ///
+    /// Returns `None` without consuming anything if the byte array can't be borrowed.
+    fn borrow_bytes(&mut self) -> Result<Option<&'de [u8]>> {
+        let input = match self.input.input.borrow_remaining() {
+            Some(input) if !self.input.flip => input,
+            _ => return Ok(None),
+        };
+        if input.first() == Some(&0) {
+            self.input.input.advance(1);
+            return Ok(Some(&[]));
+        }
+        if let Some([1, chunk @ .., len @ 1..=8]) = input.get(..1 + CHUNK_UNIT_SIZE) {
+            let len = *len as usize;
+            check_padding(&chunk[len..])?;
+            self.check_len(len)?;
+            self.input.input.advance(1 + CHUNK_UNIT_SIZE);
+            return Ok(Some(&chunk[..len]));
+        }
+        Ok(None)
+    }
+
+    /// Deserialize the `len` fields of an enum variant like a tuple.
+    ///
+    /// Running out of input at the start of a field is reported as a field count mismatch, which
+    /// is the likely cause when an enum changed. Running out within a field is still `Eof`.
+    fn deserialize_variant_fields<V>(&mut self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        struct Access<'a, B: Buf> {
+            deserializer: &'a mut Deserializer<B>,
+            len: usize,
+            expected: usize,
+        }
+
+        impl<'de, B: Input<'de>> SeqAccess<'de> for Access<'_, B> {
+            type Error = Error;
+
+            fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+            where
+                T: DeserializeSeed<'de>,
+            {
+                if self.len == 0 {
+                    return Ok(None);
+                }
+                self.len -= 1;
+                let position = self.deserializer.position();
+                let at_end = self.deserializer.input.is_empty();
+                DeserializeSeed::deserialize(seed, &mut *self.deserializer)
+                    .map(Some)
+                    .map_err(|e| match e {
+                        Error::At { source, .. } if at_end && *source == Error::Eof => {
+                            Error::VariantFieldCountMismatch {
+                                expected: self.expected,
+                            }
+                            .at(position)
+                        }
+                        e => e,
+                    })
+            }
+
+            fn size_hint(&self) -> Option<usize> {
+                Some(self.len)
+            }
+        }
+
+        let position = self.position();
+        self.nested(|de| {
+            visitor.visit_seq(Access {
+                deserializer: de,
+                len,
+                expected: len,
+            })
+        })
+        .map_err(|e| e.at(position))
+    }
+}
+
+/// The encoding of a field to [`skip`](Deserializer::skip).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Skip {
+    /// A field of a fixed number of bytes, e.g. 1 for `bool` and `u8`, 4 for `char`, `i32` and
+    /// `f32`, or 8 for `u64` and `f64`.
+    Fixed(usize),
+    /// A string or byte array.
+    Bytes,
+    /// A decimal.
+    #[cfg(feature = "decimal")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+    Decimal,
+}
+
+/// Check that the padding after the last significant byte is zero, so that each byte array has
+/// exactly one encoding.
+fn check_padding(padding: &[u8]) -> Result<()> {
+    match padding.iter().find(|&&b| b != 0) {
+        Some(&b) => Err(Error::InvalidBytesEncoding(b)),
+        None => Ok(()),
+    }
 }
 
 // Format Reference:
 // https://github.com/facebook/mysql-5.6/wiki/MyRocks-record-format#memcomparable-format
 // https://haxisnake.github.io/2020/11/06/TIDB源码学习笔记-基本类型编解码方案/
-impl<'de, 'a, B: Buf + 'de> de::Deserializer<'de> for &'a mut Deserializer<B> {
+impl<'de, B: Input<'de>> de::Deserializer<'de> for &mut Deserializer<B> {
     type Error = Error;
 
     fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
@@ -181,169 +933,245 @@ impl<'de, 'a, B: Buf + 'de> de::Deserializer<'de> for &'a mut Deserializer<B> {
     where
         V: Visitor<'de>,
     {
-        match self.input.get_u8() {
-            1 => visitor.visit_bool(true),
-            0 => visitor.visit_bool(false),
-            value => Err(Error::InvalidBoolEncoding(value)),
-        }
+        let position = self.position();
+        self.input
+            .get_u8()
+            .and_then(|v| match v {
+                1 => visitor.visit_bool(true),
+                0 => visitor.visit_bool(false),
+                value => Err(Error::InvalidBoolEncoding(value)),
+            })
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let v = (self.input.get_u8() ^ (1 << 7)) as i8;
-        visitor.visit_i8(v)
+        let position = self.position();
+        self.input
+            .get_u8()
+            .and_then(|v| visitor.visit_i8((v ^ (1u8 << 7)) as i8))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let v = (self.input.get_u16() ^ (1 << 15)) as i16;
-        visitor.visit_i16(v)
+        let position = self.position();
+        self.input
+            .get_u16()
+            .and_then(|v| visitor.visit_i16((v ^ (1u16 << 15)) as i16))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let v = (self.input.get_u32() ^ (1 << 31)) as i32;
-        visitor.visit_i32(v)
+        let position = self.position();
+        self.input
+            .get_u32()
+            .and_then(|v| visitor.visit_i32((v ^ (1u32 << 31)) as i32))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let v = (self.input.get_u64() ^ (1 << 63)) as i64;
-        visitor.visit_i64(v)
+        let position = self.position();
+        self.input
+            .get_u64()
+            .and_then(|v| visitor.visit_i64((v ^ (1u64 << 63)) as i64))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let v = (self.input.get_u128() ^ (1 << 127)) as i128;
-        visitor.visit_i128(v)
+        let position = self.position();
+        self.input
+            .get_u128()
+            .and_then(|v| visitor.visit_i128((v ^ (1u128 << 127)) as i128))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u8(self.input.get_u8())
+        let position = self.position();
+        self.input
+            .get_u8()
+            .and_then(|v| visitor.visit_u8(v))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u16(self.input.get_u16())
+        let position = self.position();
+        self.input
+            .get_u16()
+            .and_then(|v| visitor.visit_u16(v))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u32(self.input.get_u32())
+        let position = self.position();
+        self.input
+            .get_u32()
+            .and_then(|v| visitor.visit_u32(v))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u64(self.input.get_u64())
+        let position = self.position();
+        self.input
+            .get_u64()
+            .and_then(|v| visitor.visit_u64(v))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u128(self.input.get_u128())
+        let position = self.position();
+        self.input
+            .get_u128()
+            .and_then(|v| visitor.visit_u128(v))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let u = self.input.get_u32();
-        let u = if u & (1 << 31) != 0 {
-            u & !(1 << 31)
+        let position = self.position();
+        let u = self.input.get_u32().map_err(|e| e.at(position))?;
+        let u = if u & (1u32 << 31) != 0 {
+            u & !(1u32 << 31)
         } else {
             !u
         };
-        visitor.visit_f32(f32::from_bits(u))
+        visitor
+            .visit_f32(f32::from_bits(u))
+            .map_err(|e: Error| e.at(position))
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let u = self.input.get_u64();
-        let u = if u & (1 << 63) != 0 {
-            u & !(1 << 63)
+        let position = self.position();
+        let u = self.input.get_u64().map_err(|e| e.at(position))?;
+        let u = if u & (1u64 << 63) != 0 {
+            u & !(1u64 << 63)
         } else {
             !u
         };
-        visitor.visit_f64(f64::from_bits(u))
+        visitor
+            .visit_f64(f64::from_bits(u))
+            .map_err(|e: Error| e.at(position))
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let u = self.input.get_u32();
-        visitor.visit_char(char::from_u32(u).ok_or(Error::InvalidCharEncoding(u))?)
+        let position = self.position();
+        let u = self.input.get_u32().map_err(|e| e.at(position))?;
+        char::from_u32(u)
+            .ok_or(Error::InvalidCharCodePoint(u))
+            .and_then(|c| visitor.visit_char(c))
+            .map_err(|e| e.at(position))
     }
 
-    fn deserialize_str<V>(self, _visitor: V) -> Result<V::Value>
+    // Strings are split into chunks in the encoding, so they can never be borrowed from the
+    // input. Hand out an owned string and let the visitor convert it.
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        Err(Error::NotSupported("borrowed str"))
+        self.deserialize_string(visitor)
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let bytes = self.read_bytes()?;
-        visitor.visit_string(String::from_utf8(bytes)?)
+        let position = self.position();
+        if self.scratch.is_some() {
+            return self
+                .with_bytes(|bytes| match std::str::from_utf8(bytes) {
+                    Ok(s) => visitor.visit_str(s),
+                    Err(_) => Err(String::from_utf8(bytes.to_vec()).unwrap_err().into()),
+                })
+                .map_err(|e| e.at(position));
+        }
+        self.read_bytes()
+            .and_then(|bytes| Ok(String::from_utf8(bytes)?))
+            .and_then(|s| visitor.visit_string(s))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let bytes = self.read_bytes()?;
-        visitor.visit_bytes(&bytes)
+        // a single chunk is borrowed from a slice input, e.g. for `&'de [u8]` fields. longer or
+        // flipped byte arrays must be decoded into a buffer first.
+        let position = self.position();
+        match self.borrow_bytes() {
+            Ok(Some(bytes)) => visitor.visit_borrowed_bytes(bytes),
+            Ok(None) => self.with_bytes(|bytes| visitor.visit_bytes(bytes)),
+            Err(e) => Err(e),
+        }
+        .map_err(|e| e.at(position))
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let bytes = self.read_bytes()?;
-        visitor.visit_byte_buf(bytes)
+        let position = self.position();
+        self.read_bytes()
+            .and_then(|bytes| visitor.visit_byte_buf(bytes))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self.input.get_u8() {
-            0 => visitor.visit_none(),
-            1 => visitor.visit_some(self),
-            t => Err(Error::InvalidTagEncoding(t as usize)),
-        }
+        let position = self.position();
+        self.input
+            .get_u8()
+            .and_then(|v| match v {
+                0 => visitor.visit_none(),
+                1 => self.nested(|de| visitor.visit_some(de)),
+                t => Err(Error::InvalidTagEncoding(t as usize)),
+            })
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_unit()
+        let position = self.position();
+        visitor.visit_unit().map_err(|e: Error| e.at(position))
     }
 
     fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
@@ -360,35 +1188,56 @@ impl<'de, 'a, B: Buf + 'de> de::Deserializer<'de> for &'a mut Deserializer<B> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        let position = self.position();
+        visitor
+            .visit_newtype_struct(&mut *self)
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if self.seq_framing == SeqFraming::LengthPrefixed {
+            // the elements follow without framing, like those of a tuple
+            let position = self.position();
+            let len = self.input.get_u32().map_err(|e| e.at(position))?;
+            return self.deserialize_tuple(len as usize, visitor);
+        }
+
+        // The number of elements is only known at the end marker, and any bound derived from the
+        // remaining bytes would be far too large for most element types, so there is no size
+        // hint. Length-prefixed sequences above have an exact one.
         struct Access<'a, B: Buf> {
             deserializer: &'a mut Deserializer<B>,
         }
-        impl<'de, 'a, B: Buf + 'de> SeqAccess<'de> for Access<'a, B> {
+        impl<'de, B: Input<'de>> SeqAccess<'de> for Access<'_, B> {
             type Error = Error;
 
             fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
             where
                 T: DeserializeSeed<'de>,
             {
-                match self.deserializer.input.get_u8() {
+                let position = self.deserializer.position();
+                let marker = self
+                    .deserializer
+                    .input
+                    .get_u8()
+                    .map_err(|e| e.at(position))?;
+                match marker {
                     1 => Ok(Some(DeserializeSeed::deserialize(
                         seed,
                         &mut *self.deserializer,
                     )?)),
                     0 => Ok(None),
-                    value => Err(Error::InvalidSeqEncoding(value)),
+                    value => Err(Error::InvalidSeqEncoding(value).at(position)),
                 }
             }
         }
 
-        visitor.visit_seq(Access { deserializer: self })
+        let position = self.position();
+        self.nested(|de| visitor.visit_seq(Access { deserializer: de }))
+            .map_err(|e| e.at(position))
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
@@ -400,7 +1249,7 @@ impl<'de, 'a, B: Buf + 'de> de::Deserializer<'de> for &'a mut Deserializer<B> {
             len: usize,
         }
 
-        impl<'de, 'a, B: Buf + 'de> SeqAccess<'de> for Access<'a, B> {
+        impl<'de, B: Input<'de>> SeqAccess<'de> for Access<'_, B> {
             type Error = Error;
 
             fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
@@ -421,10 +1270,14 @@ impl<'de, 'a, B: Buf + 'de> de::Deserializer<'de> for &'a mut Deserializer<B> {
             }
         }
 
-        visitor.visit_seq(Access {
-            deserializer: self,
-            len,
+        let position = self.position();
+        self.nested(|de| {
+            visitor.visit_seq(Access {
+                deserializer: de,
+                len,
+            })
         })
+        .map_err(|e| e.at(position))
     }
 
     fn deserialize_tuple_struct<V>(
@@ -461,27 +1314,54 @@ impl<'de, 'a, B: Buf + 'de> de::Deserializer<'de> for &'a mut Deserializer<B> {
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        impl<'de, 'a, B: Buf + 'de> EnumAccess<'de> for &'a mut Deserializer<B> {
+        struct Access<'a, B: Buf> {
+            deserializer: &'a mut Deserializer<B>,
+            variants: &'static [&'static str],
+        }
+
+        impl<'de, 'a, B: Input<'de>> EnumAccess<'de> for Access<'a, B> {
             type Error = Error;
-            type Variant = Self;
+            type Variant = &'a mut Deserializer<B>;
 
             fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
             where
                 V: DeserializeSeed<'de>,
             {
-                let idx = self.input.get_u8() as u32;
+                let idx = if self.deserializer.named_variants {
+                    let name = self.deserializer.read_bytes()?;
+                    match self.variants.iter().position(|v| v.as_bytes() == name) {
+                        Some(idx) => idx as u32,
+                        None => {
+                            let name = String::from_utf8_lossy(&name);
+                            return Err(de::Error::unknown_variant(&name, self.variants));
+                        }
+                    }
+                } else {
+                    let idx = self.deserializer.input.get_u8()? as u32;
+                    if idx as usize >= self.variants.len() {
+                        return Err(Error::UnknownVariant(idx));
+                    }
+                    idx
+                };
                 let val: Result<_> = seed.deserialize(idx.into_deserializer());
-                Ok((val?, self))
+                Ok((val?, self.deserializer))
             }
         }
 
-        visitor.visit_enum(self)
+        let position = self.position();
+        self.nested(|de| {
+            visitor.visit_enum(Access {
+                deserializer: de,
+                variants,
+            })
+        })
+        .map_err(|e| e.at(position))
     }
 
     fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value>
@@ -497,11 +1377,15 @@ impl<'de, 'a, B: Buf + 'de> de::Deserializer<'de> for &'a mut Deserializer<B> {
     {
         Err(Error::NotSupported("deserialize_ignored_any"))
     }
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
 }
 
 // `VariantAccess` is provided to the `Visitor` to give it the ability to see
 // the content of the single variant that it decided to deserialize.
-impl<'de, 'a, B: Buf + 'de> VariantAccess<'de> for &'a mut Deserializer<B> {
+impl<'de, B: Input<'de>> VariantAccess<'de> for &mut Deserializer<B> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
@@ -519,58 +1403,389 @@ impl<'de, 'a, B: Buf + 'de> VariantAccess<'de> for &'a mut Deserializer<B> {
     where
         V: Visitor<'de>,
     {
-        serde::de::Deserializer::deserialize_tuple(self, len, visitor)
+        self.deserialize_variant_fields(len, visitor)
     }
 
     fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        serde::de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+        self.deserialize_variant_fields(fields.len(), visitor)
     }
 }
 
 impl<B: Buf> Deserializer<B> {
-    /// Deserialize a decimal value.
+    /// Deserialize a byte array of `len` bytes serialized by
+    /// [`Serializer::serialize_fixed_bytes`].
     ///
-    /// # Example
-    /// ```
-    /// let buf = [0x15];
-    /// let mut de = memcomparable::Deserializer::new(&buf[..]);
-    /// let v = de.deserialize_decimal().unwrap();
-    /// assert_eq!(v.to_string(), "0");
+    /// [`Serializer::serialize_fixed_bytes`]: crate::Serializer::serialize_fixed_bytes
+    pub fn deserialize_fixed_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        let position = self.position();
+        if self.input.input.remaining() < len {
+            return Err(Error::Eof.at(position));
+        }
+        let mut bytes = vec![0; len];
+        self.input.copy_to_slice(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Deserialize a string of `width` bytes serialized by [`Serializer::serialize_fixed_str`],
+    /// trimming the zero padding.
+    ///
+    /// [`Serializer::serialize_fixed_str`]: crate::Serializer::serialize_fixed_str
+    pub fn deserialize_fixed_str(&mut self, width: usize) -> Result<String> {
+        let position = self.position();
+        let mut bytes = self.deserialize_fixed_bytes(width)?;
+        let len = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        bytes.truncate(len);
+        String::from_utf8(bytes).map_err(|e| Error::from(e).at(position))
+    }
+
+    /// Deserialize `len` bools serialized by [`Serializer::serialize_bool_slice`].
+    ///
+    /// Returns [`Error::InvalidBoolEncoding`] with the last byte if its padding bits are not
+    /// zero.
+    ///
+    /// [`Serializer::serialize_bool_slice`]: crate::Serializer::serialize_bool_slice
+    pub fn deserialize_bool_slice(&mut self, len: usize) -> Result<Vec<bool>> {
+        let position = self.position();
+        let bytes = self.deserialize_fixed_bytes(len.div_ceil(8))?;
+        if let Some(&last) = bytes.last() {
+            let padding = bytes.len() * 8 - len;
+            if last & ((1 << padding) - 1) != 0 {
+                let position = position + bytes.len() - 1;
+                return Err(Error::InvalidBoolEncoding(last).at(position));
+            }
+        }
+        Ok((0..len)
+            .map(|i| bytes[i / 8] & (0x80 >> (i % 8)) != 0)
+            .collect())
+    }
+
+    /// Deserialize an IP address serialized by [`Serializer::serialize_ip_addr`].
+    ///
+    /// [`Serializer::serialize_ip_addr`]: crate::Serializer::serialize_ip_addr
+    pub fn deserialize_ip_addr(&mut self) -> Result<IpAddr> {
+        let position = self.position();
+        self.read_ip_addr().map_err(|e| e.at(position))
+    }
+
+    /// Deserialize a nullable string serialized by [`Serializer::serialize_nullable_str`].
+    ///
+    /// [`Serializer::serialize_nullable_str`]: crate::Serializer::serialize_nullable_str
+    pub fn deserialize_nullable_str(&mut self) -> Result<Option<String>> {
+        let position = self.position();
+        self.read_nullable_str().map_err(|e| e.at(position))
+    }
+
+    fn read_nullable_str(&mut self) -> Result<Option<String>> {
+        match self.input.get_u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(String::new())),
+            2 => {
+                let mut bytes = vec![];
+                self.append_chunks(&mut bytes, 0)?;
+                Ok(Some(String::from_utf8(bytes)?))
+            }
+            v => Err(Error::InvalidBytesEncoding(v)),
+        }
+    }
+
+    /// Deserialize a nullable value serialized by [`Serializer::serialize_nullable`] with the
+    /// same `nulls_last`.
+    ///
+    /// [`Serializer::serialize_nullable`]: crate::Serializer::serialize_nullable
+    pub fn deserialize_nullable<'de, T: DeserializeOwned>(
+        &mut self,
+        nulls_last: bool,
+    ) -> Result<Option<T>>
+    where
+        B: Input<'de>,
+    {
+        let position = self.position();
+        let null = if nulls_last { 0xff } else { 0 };
+        match self.input.get_u8().map_err(|e| e.at(position))? {
+            v if v == null => Ok(None),
+            1 => T::deserialize(&mut *self).map(Some),
+            t => Err(Error::InvalidTagEncoding(t as usize).at(position)),
+        }
+    }
+
+    /// Deserialize a tag and a value serialized by [`Serializer::serialize_tagged`].
+    ///
+    /// If the type of the value depends on the tag, deserialize the tag as a `u8` first and then
+    /// the value of the matching type.
+    ///
+    /// [`Serializer::serialize_tagged`]: crate::Serializer::serialize_tagged
+    pub fn deserialize_tagged<'de, T: DeserializeOwned>(&mut self) -> Result<(u8, T)>
+    where
+        B: Input<'de>,
+    {
+        let position = self.position();
+        let tag = self.input.get_u8().map_err(|e| e.at(position))?;
+        let value = T::deserialize(&mut *self)?;
+        Ok((tag, value))
+    }
+
+    /// Deserialize a dynamically typed value serialized by [`Serializer::serialize_dynamic`].
+    ///
+    /// [`Serializer::serialize_dynamic`]: crate::Serializer::serialize_dynamic
+    pub fn deserialize_dynamic<'de>(&mut self) -> Result<DynValue>
+    where
+        B: Input<'de>,
+    {
+        let position = self.position();
+        Ok(match self.input.get_u8().map_err(|e| e.at(position))? {
+            DynValue::NULL => DynValue::Null,
+            DynValue::BOOL => DynValue::Bool(Deserialize::deserialize(&mut *self)?),
+            DynValue::INT => DynValue::Int(Deserialize::deserialize(&mut *self)?),
+            DynValue::FLOAT => DynValue::Float(Deserialize::deserialize(&mut *self)?),
+            DynValue::STRING => DynValue::String(Deserialize::deserialize(&mut *self)?),
+            DynValue::BYTES => {
+                let position = self.position();
+                DynValue::Bytes(self.read_bytes().map_err(|e| e.at(position))?)
+            }
+            t => return Err(Error::InvalidTagEncoding(t as usize).at(position)),
+        })
+    }
+
+    /// Deserialize a row of `columns` nullable columns serialized by
+    /// [`Serializer::serialize_row_with_nulls`].
+    ///
+    /// [`Serializer::serialize_row_with_nulls`]: crate::Serializer::serialize_row_with_nulls
+    pub fn deserialize_row_with_nulls<'de>(
+        &mut self,
+        columns: usize,
+    ) -> Result<Vec<Option<DynValue>>>
+    where
+        B: Input<'de>,
+    {
+        self.deserialize_bool_slice(columns)?
+            .into_iter()
+            .map(|present| present.then(|| self.deserialize_dynamic()).transpose())
+            .collect()
+    }
+
+    /// Deserialize a timestamp serialized by [`Serializer::serialize_timestamp_millis`].
+    ///
+    /// [`Serializer::serialize_timestamp_millis`]: crate::Serializer::serialize_timestamp_millis
+    pub fn deserialize_timestamp_millis<'de>(&mut self) -> Result<i64>
+    where
+        B: Input<'de>,
+    {
+        i64::deserialize(self)
+    }
+
+    /// Deserialize a timestamp serialized by [`Serializer::serialize_timestamp_micros`].
+    ///
+    /// [`Serializer::serialize_timestamp_micros`]: crate::Serializer::serialize_timestamp_micros
+    pub fn deserialize_timestamp_micros<'de>(&mut self) -> Result<i64>
+    where
+        B: Input<'de>,
+    {
+        i64::deserialize(self)
+    }
+
+    /// Deserialize a `chrono::TimeDelta` serialized by [`Serializer::serialize_time_delta`].
+    ///
+    /// Returns an error if the nanoseconds are not below one second, or if the delta is out of
+    /// the range of `TimeDelta`.
+    ///
+    /// [`Serializer::serialize_time_delta`]: crate::Serializer::serialize_time_delta
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    pub fn deserialize_time_delta<'de>(&mut self) -> Result<chrono::TimeDelta>
+    where
+        B: Input<'de>,
+    {
+        let position = self.position();
+        let secs = i64::deserialize(&mut *self)?;
+        let nanos = u32::deserialize(&mut *self)?;
+        chrono::TimeDelta::new(secs, nanos)
+            .ok_or_else(|| de::Error::custom("time delta out of range"))
+            .map_err(|e: Error| e.at(position))
+    }
+
+    /// Deserialize a socket address serialized by [`Serializer::serialize_socket_addr`].
+    ///
+    /// The `flowinfo` and `scope_id` of v6 addresses are not encoded, and are set to zero.
+    ///
+    /// [`Serializer::serialize_socket_addr`]: crate::Serializer::serialize_socket_addr
+    pub fn deserialize_socket_addr(&mut self) -> Result<SocketAddr> {
+        let position = self.position();
+        self.read_ip_addr()
+            .and_then(|ip| Ok(SocketAddr::new(ip, self.input.get_u16()?)))
+            .map_err(|e| e.at(position))
+    }
+
+    fn read_ip_addr(&mut self) -> Result<IpAddr> {
+        match self.input.get_u8()? {
+            0 => {
+                let mut octets = [0; 4];
+                self.input.copy_to_slice(&mut octets)?;
+                Ok(Ipv4Addr::from(octets).into())
+            }
+            1 => {
+                let mut octets = [0; 16];
+                self.input.copy_to_slice(&mut octets)?;
+                Ok(Ipv6Addr::from(octets).into())
+            }
+            t => Err(Error::InvalidTagEncoding(t as usize)),
+        }
+    }
+
+    /// Deserialize a map serialized by [`Serializer::serialize_sorted_map`].
+    ///
+    /// [`Serializer::serialize_sorted_map`]: crate::Serializer::serialize_sorted_map
+    pub fn deserialize_sorted_map<'de, K, V, S>(&mut self) -> Result<HashMap<K, V, S>>
+    where
+        B: Input<'de>,
+        K: DeserializeOwned + Eq + Hash,
+        V: DeserializeOwned,
+        S: BuildHasher + Default,
+    {
+        let entries = Vec::<(K, V)>::deserialize(self)?;
+        Ok(entries.into_iter().collect())
+    }
+
+    /// Deserialize a value serialized by [`Serializer::serialize_framed`].
+    ///
+    /// [`Serializer::serialize_framed`]: crate::Serializer::serialize_framed
+    pub fn deserialize_framed<T: DeserializeOwned>(&mut self) -> Result<T> {
+        let position = self.position();
+        let len = self.input.get_u32().map_err(|e| e.at(position))? as usize;
+        if self.input.input.remaining() < len {
+            return Err(Error::Eof.at(position));
+        }
+        let mut bytes = vec![0; len];
+        self.input.copy_to_slice(&mut bytes)?;
+
+        let mut inner = Deserializer::new(bytes.as_slice());
+        inner.human_readable = self.human_readable;
+        inner.strict = self.strict;
+        inner.max_alloc = self.max_alloc;
+        inner.depth = self.depth;
+        inner.max_depth = self.max_depth;
+        inner.seq_framing = self.seq_framing;
+        inner.named_variants = self.named_variants;
+        // report positions relative to the outer input
+        let offset = position + 4;
+        T::deserialize(&mut inner)
+            .and_then(|t| {
+                inner.end()?;
+                Ok(t)
+            })
+            .map_err(|e| match e {
+                Error::At { position, source } => Error::At {
+                    position: offset + position,
+                    source,
+                },
+                e => e.at(position),
+            })
+    }
+
+    /// Deserialize a decimal value.
+    ///
+    /// # Example
+    /// ```
+    /// let buf = [0x15];
+    /// let mut de = memcomparable::Deserializer::new(&buf[..]);
+    /// let v = de.deserialize_decimal().unwrap();
+    /// assert_eq!(v.to_string(), "0");
     /// ```
     #[cfg(feature = "decimal")]
     #[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
     pub fn deserialize_decimal(&mut self) -> Result<Decimal> {
+        let position = self.position();
+        self.read_decimal().map_err(|e| e.at(position))
+    }
+
+    #[cfg(feature = "decimal")]
+    fn read_decimal(&mut self) -> Result<Decimal> {
         // decode exponent
-        let flag = self.input.get_u8();
+        let flag = self.input.get_u8()?;
         let exponent = match flag {
             0x07 => return Ok(Decimal::NegInf),
-            0x08 => !self.input.get_u8() as i8,
-            0x09..=0x13 => (0x13 - flag) as i8,
-            0x14 => -(self.input.get_u8() as i8),
+            0x08 => !self.input.get_u8()? as i8 as i32,
+            0x09..=0x13 => (0x13 - flag) as i32,
+            0x14 => -(self.input.get_u8()? as i8 as i32),
             0x15 => return Ok(Decimal::ZERO),
-            0x16 => -!(self.input.get_u8() as i8),
-            0x17..=0x21 => (flag - 0x17) as i8,
-            0x22 => self.input.get_u8() as i8,
+            0x16 => -(!(self.input.get_u8()? as i8) as i32),
+            0x17..=0x21 => (flag - 0x17) as i32,
+            0x22 => self.input.get_u8()? as i8 as i32,
             0x23 => return Ok(Decimal::Inf),
             0x24 => return Ok(Decimal::NaN),
             b => return Err(Error::InvalidDecimalEncoding(b)),
         };
-        // decode mantissa
         let neg = (0x07..0x15).contains(&flag);
+        self.read_decimal_significand(flag, neg, exponent)
+            .map(Decimal::from)
+    }
+
+    /// Deserialize a decimal value written by
+    /// [`serialize_finite_decimal`](crate::Serializer::serialize_finite_decimal).
+    ///
+    /// Returns an error if the value is out of the range of `rust_decimal::Decimal`.
+    ///
+    /// # Example
+    /// ```
+    /// let buf = [0x8f, 0x14];
+    /// let mut de = memcomparable::Deserializer::new(&buf[..]);
+    /// let v = de.deserialize_finite_decimal().unwrap();
+    /// assert_eq!(v.to_string(), "0.001");
+    /// ```
+    #[cfg(feature = "decimal")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+    pub fn deserialize_finite_decimal(&mut self) -> Result<rust_decimal::Decimal> {
+        let position = self.position();
+        self.read_finite_decimal().map_err(|e| e.at(position))
+    }
+
+    #[cfg(feature = "decimal")]
+    fn read_finite_decimal(&mut self) -> Result<rust_decimal::Decimal> {
+        let flag = self.input.get_u8()?;
+        let (neg, exponent) = match flag {
+            FINITE_DECIMAL_ZERO => return Ok(rust_decimal::Decimal::ZERO),
+            0x60..FINITE_DECIMAL_ZERO => (true, FINITE_DECIMAL_NEG_BASE as i32 - flag as i32),
+            0x81..=0xa0 => (false, flag as i32 - FINITE_DECIMAL_POS_BASE as i32),
+            b => return Err(Error::InvalidDecimalEncoding(b)),
+        };
+        self.read_decimal_significand(flag, neg, exponent)
+    }
+
+    /// Decode the significand following the tag `flag` of a decimal.
+    #[cfg(feature = "decimal")]
+    fn read_decimal_significand(
+        &mut self,
+        flag: u8,
+        neg: bool,
+        exponent: i32,
+    ) -> Result<rust_decimal::Decimal> {
+        // corrupted input may describe a mantissa or scale that does not fit
+        let overflow = || Error::InvalidDecimalEncoding(flag);
+
+        // decode mantissa
         let mut mantissa: i128 = 0;
-        let mut mlen = 0i8;
+        let mut mlen = 0i32;
         loop {
-            let mut b = self.input.get_u8();
+            let mut b = self.input.get_u8().map_err(|e| match e {
+                Error::Eof => Error::TruncatedDecimal,
+                e => e,
+            })?;
             if neg {
                 b = !b;
             }
             let x = b / 2;
-            mantissa = mantissa * 100 + x as i128;
+            mantissa = mantissa
+                .checked_mul(100)
+                .and_then(|m| m.checked_add(x as i128))
+                .ok_or_else(overflow)?;
             mlen += 1;
             if b & 1 == 0 {
+                // the serializer strips trailing zeros
+                if self.strict && x == 0 {
+                    return Err(Error::NonCanonical("trailing zeros in decimal significand"));
+                }
                 break;
             }
         }
@@ -580,7 +1795,7 @@ impl<B: Buf> Deserializer<B> {
         if scale <= 0 {
             // e.g. 1(mantissa) + 2(exponent) (which is 100).
             for _i in 0..-scale {
-                mantissa *= 10;
+                mantissa = mantissa.checked_mul(10).ok_or_else(overflow)?;
             }
             scale = 0;
         } else if mantissa % 10 == 0 {
@@ -593,37 +1808,217 @@ impl<B: Buf> Deserializer<B> {
         if neg {
             mantissa = -mantissa;
         }
-        Ok(rust_decimal::Decimal::from_i128_with_scale(mantissa, scale as u32).into())
+        rust_decimal::Decimal::try_from_i128_with_scale(mantissa, scale as u32)
+            .map_err(|_| overflow())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
 
     use super::*;
+    use crate::NanOrder;
 
     #[test]
     fn test_unit() {
         assert_eq!(from_slice::<()>(&[]), Ok(()));
-        assert_eq!(from_slice::<()>(&[0]), Err(Error::TrailingCharacters));
+        assert_eq!(
+            from_slice::<()>(&[0]),
+            Err(Error::TrailingCharacters { remaining: 1 }.at(0))
+        );
 
         #[derive(Debug, PartialEq, Eq, Deserialize)]
         struct UnitStruct;
         assert_eq!(from_slice::<UnitStruct>(&[]).unwrap(), UnitStruct);
     }
 
+    #[test]
+    fn test_phantom_data() {
+        use std::marker::PhantomData;
+
+        assert_eq!(from_slice::<PhantomData<u32>>(&[]), Ok(PhantomData));
+        assert_eq!(from_slice::<[(); 3]>(&[]), Ok([(); 3]));
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Typed<T> {
+            id: u32,
+            marker: PhantomData<T>,
+            name: String,
+        }
+
+        let bytes = crate::to_vec(&(7u32, "key")).unwrap();
+        assert_eq!(
+            from_slice::<Typed<u64>>(&bytes),
+            Ok(Typed {
+                id: 7,
+                marker: PhantomData,
+                name: "key".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_trailing_characters() {
+        let err = from_slice::<u8>(&[1, 2, 3]).unwrap_err();
+        assert_eq!(err, Error::TrailingCharacters { remaining: 2 }.at(1));
+        assert_eq!(
+            err.to_string(),
+            "trailing characters: 2 bytes left (at position 1)"
+        );
+    }
+
     #[test]
     fn test_bool() {
         assert_eq!(from_slice::<bool>(&[0]), Ok(false));
         assert_eq!(from_slice::<bool>(&[1]), Ok(true));
-        assert_eq!(from_slice::<bool>(&[2]), Err(Error::InvalidBoolEncoding(2)));
+        assert_eq!(
+            from_slice::<bool>(&[2]).map_err(Error::into_inner),
+            Err(Error::InvalidBoolEncoding(2))
+        );
+    }
+
+    #[test]
+    fn test_char() {
+        assert_eq!(from_slice::<char>(&[0, 0, 0, b'a']), Ok('a'));
+        assert_eq!(from_slice::<char>(&[0, 0x10, 0xff, 0xff]), Ok('\u{10ffff}'));
+
+        let err = from_slice::<char>(&[0, 0, 0xd8, 0]).unwrap_err();
+        assert_eq!(
+            err.clone().into_inner(),
+            Error::InvalidCharCodePoint(0xd800)
+        );
+        assert!(err.to_string().contains("surrogate"), "{err}");
+
+        let err = from_slice::<char>(&[0, 0x11, 0, 0]).unwrap_err();
+        assert_eq!(
+            err.clone().into_inner(),
+            Error::InvalidCharCodePoint(0x110000)
+        );
+        assert!(err.to_string().contains("out of range"), "{err}");
+
+        let err = from_slice::<char>(&[0, 0, 0xdf, 0xff]).unwrap_err();
+        assert_eq!(err.into_inner(), Error::InvalidCharCodePoint(0xdfff));
+        assert_eq!(
+            from_slice_desc::<char>(&[0xff, 0xff, 0x27, 0xff]).map_err(Error::into_inner),
+            Err(Error::InvalidCharCodePoint(0xd800))
+        );
+    }
+
+    #[test]
+    fn test_char_roundtrip() {
+        for c in [
+            '\0',
+            '\u{7f}',
+            '\u{80}',
+            '\u{d7ff}',
+            '\u{e000}',
+            '\u{ffff}',
+            '\u{10000}',
+            char::MAX,
+        ] {
+            let bytes = crate::to_vec(&c).unwrap();
+            assert_eq!(from_slice(&bytes), Ok(c));
+            let bytes = crate::to_vec_desc(&c).unwrap();
+            assert_eq!(from_slice_desc(&bytes), Ok(c));
+        }
+    }
+
+    #[test]
+    fn test_error_position() {
+        #[derive(Debug, Deserialize)]
+        struct Test {
+            _a: u16,
+            _b: u8,
+            _c: bool,
+        }
+        let err = from_slice::<Test>(&[0x12, 0x34, 0x56, 2]).unwrap_err();
+        assert_eq!(err.position(), Some(3));
+        assert_eq!(err.into_inner(), Error::InvalidBoolEncoding(2));
+
+        // the position of the innermost failing value is reported
+        let err = from_slice::<Vec<String>>(&[1, 0, 1, 3]).unwrap_err();
+        assert_eq!(err.position(), Some(3));
+        assert_eq!(err.into_inner(), Error::InvalidBytesEncoding(3));
+    }
+
+    #[test]
+    fn test_signed_edges() {
+        macro_rules! check_roundtrip {
+            ($ty:ty) => {
+                for v in [<$ty>::MIN, -1, 0, 1, <$ty>::MAX] {
+                    let bytes = crate::to_vec(&v).unwrap();
+                    assert_eq!(from_slice::<$ty>(&bytes), Ok(v));
+                }
+            };
+        }
+        check_roundtrip!(i8);
+        check_roundtrip!(i16);
+        check_roundtrip!(i32);
+        check_roundtrip!(i64);
+        check_roundtrip!(i128);
+    }
+
+    #[test]
+    fn test_float_infinity() {
+        for v in [f64::NEG_INFINITY, f64::MIN, f64::MAX, f64::INFINITY] {
+            assert_eq!(from_slice(&crate::to_vec(&v).unwrap()), Ok(v));
+            assert_eq!(from_slice_desc(&crate::to_vec_desc(&v).unwrap()), Ok(v));
+        }
+        for v in [f32::NEG_INFINITY, f32::MIN, f32::MAX, f32::INFINITY] {
+            assert_eq!(from_slice(&crate::to_vec(&v).unwrap()), Ok(v));
+            assert_eq!(from_slice_desc(&crate::to_vec_desc(&v).unwrap()), Ok(v));
+        }
+    }
+
+    #[test]
+    fn test_nan_order() {
+        for reverse in [false, true] {
+            let config = Config::new().nan_order(NanOrder::Low).reverse(reverse);
+            let mut ser = crate::Serializer::with_config(vec![], config);
+            (f64::NAN, f32::NAN, -1.5f64).serialize(&mut ser).unwrap();
+            let bytes = ser.into_inner();
+
+            let mut de = Deserializer::with_config(bytes.as_slice(), config);
+            let (a, b, c) = <(f64, f32, f64)>::deserialize(&mut de).unwrap();
+            assert!(a.is_nan() && b.is_nan());
+            assert_eq!(c, -1.5);
+        }
+    }
+
+    #[test]
+    fn test_float_subnormal() {
+        for bits in [1, 0x1234, (1 << 52) - 1] {
+            for v in [f64::from_bits(bits), -f64::from_bits(bits)] {
+                let bytes = crate::to_vec(&v).unwrap();
+                assert_eq!(from_slice::<f64>(&bytes).unwrap().to_bits(), v.to_bits());
+            }
+        }
+        let v = f32::from_bits(1);
+        assert_eq!(from_slice::<f32>(&crate::to_vec(&v).unwrap()), Ok(v));
+
+        // negative zero decodes as positive zero
+        let bytes = crate::to_vec(&-0.0f64).unwrap();
+        assert!(from_slice::<f64>(&bytes).unwrap().is_sign_positive());
     }
 
     #[test]
     fn test_option() {
         assert_eq!(from_slice::<Option<u8>>(&[0]).unwrap(), None);
         assert_eq!(from_slice::<Option<u8>>(&[1, 0x12]).unwrap(), Some(0x12));
+
+        for value in [None, Some(None), Some(Some(0u8)), Some(Some(0xff))] {
+            let bytes = crate::to_vec(&value).unwrap();
+            assert_eq!(from_slice(&bytes), Ok(value));
+        }
+        for value in [None, Some(String::new()), Some("option".to_string())] {
+            let bytes = crate::to_vec(&value).unwrap();
+            assert_eq!(from_slice(&bytes), Ok(value));
+        }
+        assert_eq!(
+            from_slice::<Option<Option<u8>>>(&[1, 2]).map_err(Error::into_inner),
+            Err(Error::InvalidTagEncoding(2))
+        );
     }
 
     #[test]
@@ -670,6 +2065,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_wide_tuple() {
+        // 20 columns, more than serde implements for a flat tuple
+        type Key = (
+            (u8, i16, u32, i64, bool, char, String, Option<u8>),
+            (f32, f64, u128, i128, Vec<u8>, (), i8, u16),
+            (String, Option<String>, i32, u64),
+        );
+        let key: Key = (
+            (1, -2, 3, -4, true, 'x', "seven".into(), Some(8)),
+            (9.5, -10.25, 11, -12, vec![13], (), -15, 16),
+            ("".into(), None, -19, 20),
+        );
+
+        let bytes = crate::to_vec(&key).unwrap();
+        assert_eq!(from_slice::<Key>(&bytes), Ok(key.clone()));
+
+        // the nesting does not show in the encoding
+        let mut ser = crate::Serializer::new(vec![]);
+        serde::Serialize::serialize(&(key.0, key.1 .0), &mut ser).unwrap();
+        serde::Serialize::serialize(&(key.1 .1, key.1 .2, key.1 .3), &mut ser).unwrap();
+        serde::Serialize::serialize(&(key.1 .4, key.1 .5, key.1 .6, key.1 .7), &mut ser).unwrap();
+        serde::Serialize::serialize(&key.2, &mut ser).unwrap();
+        assert_eq!(ser.into_inner(), bytes);
+    }
+
     #[test]
     fn test_vec() {
         assert_eq!(
@@ -677,11 +2098,76 @@ mod tests {
             vec![1, 2, 3]
         );
         assert_eq!(
-            from_slice::<Vec<u8>>(&[1, 0x01, 2]),
+            from_slice::<Vec<u8>>(&[1, 0x01, 2]).map_err(Error::into_inner),
             Err(Error::InvalidSeqEncoding(2))
         );
     }
 
+    #[test]
+    fn test_vec_of_units() {
+        let mut last = vec![];
+        for len in [0, 1, 3] {
+            let value = vec![(); len];
+            let bytes = crate::to_vec(&value).unwrap();
+            let mut expected = vec![1; len];
+            expected.push(0);
+            assert_eq!(bytes, expected);
+            assert_eq!(from_slice::<Vec<()>>(&bytes).map(|v| v.len()), Ok(len));
+            assert!(bytes > last);
+            last = bytes;
+        }
+    }
+
+    #[test]
+    fn test_vec_of_wrappers() {
+        assert_eq!(
+            from_slice::<Vec<Option<u8>>>(&[1, 1, 0x01, 1, 0, 0]),
+            Ok(vec![Some(1), None])
+        );
+        assert_eq!(from_slice::<Vec<Option<u8>>>(&[1, 0, 0]), Ok(vec![None]));
+        assert_eq!(
+            from_slice::<Vec<Option<Option<u8>>>>(&[1, 1, 0, 0]),
+            Ok(vec![Some(None)])
+        );
+
+        let bytes = crate::to_vec(&vec!["a", "bc"]).unwrap();
+        let boxed: Vec<Box<str>> = from_slice(&bytes).unwrap();
+        assert_eq!(boxed, ["a".into(), "bc".into()]);
+    }
+
+    #[test]
+    fn test_num_wrappers() {
+        use std::num::{Saturating, Wrapping};
+
+        // serde serializes these as the bare integer rather than as a newtype struct
+        for v in [0u32, 1, 0x1234_5678, u32::MAX] {
+            let bytes = crate::to_vec(&Wrapping(v)).unwrap();
+            assert_eq!(bytes, crate::to_vec(&v).unwrap());
+            assert_eq!(from_slice(&bytes), Ok(Wrapping(v)));
+        }
+        for v in [i64::MIN, -1, 0, 1, i64::MAX] {
+            let bytes = crate::to_vec(&Saturating(v)).unwrap();
+            assert_eq!(bytes, crate::to_vec(&v).unwrap());
+            assert_eq!(from_slice(&bytes), Ok(Saturating(v)));
+        }
+
+        let ints = [i64::MIN, -2, -1, 0, 1, i64::MAX];
+        for a in ints {
+            for b in ints {
+                let (ea, eb) = (
+                    crate::to_vec(&Saturating(a)).unwrap(),
+                    crate::to_vec(&Saturating(b)).unwrap(),
+                );
+                assert_eq!(a.cmp(&b), ea.cmp(&eb));
+                let (ea, eb) = (
+                    crate::to_vec(&Wrapping(a as u32)).unwrap(),
+                    crate::to_vec(&Wrapping(b as u32)).unwrap(),
+                );
+                assert_eq!((a as u32).cmp(&(b as u32)), ea.cmp(&eb));
+            }
+        }
+    }
+
     #[test]
     fn test_enum() {
         #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
@@ -707,6 +2193,128 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_named_variants() {
+        mod v1 {
+            #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+            pub enum Status {
+                Pending,
+                Active(u8),
+                Closed { code: i16 },
+            }
+        }
+        mod v2 {
+            // reordered, with a new variant
+            #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+            pub enum Status {
+                Archived,
+                Closed { code: i16 },
+                Active(u8),
+                Pending,
+            }
+        }
+
+        fn encode(value: &impl Serialize) -> Vec<u8> {
+            let mut ser =
+                crate::Serializer::with_config(vec![], Config::new().named_variants(true));
+            value.serialize(&mut ser).unwrap();
+            ser.into_inner()
+        }
+        fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+            let mut de = Deserializer::with_config(bytes, Config::new().named_variants(true));
+            T::deserialize(&mut de)
+        }
+
+        let cases = [
+            (v1::Status::Pending, v2::Status::Pending),
+            (v1::Status::Active(7), v2::Status::Active(7)),
+            (
+                v1::Status::Closed { code: -1 },
+                v2::Status::Closed { code: -1 },
+            ),
+        ];
+        for (old, new) in &cases {
+            let bytes = encode(old);
+            assert_eq!(bytes, encode(new));
+            assert_eq!(decode::<v1::Status>(&bytes).as_ref(), Ok(old));
+            assert_eq!(decode::<v2::Status>(&bytes).as_ref(), Ok(new));
+        }
+        assert_eq!(
+            encode(&v1::Status::Pending),
+            crate::to_vec(&"Pending").unwrap()
+        );
+
+        // variants sort by name rather than by declaration order
+        let encodings: Vec<_> = cases.iter().map(|(old, _)| encode(old)).collect();
+        assert!(encodings[1] < encodings[2] && encodings[2] < encodings[0]);
+
+        let err = decode::<v1::Status>(&encode(&v2::Status::Archived)).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown variant `Archived`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_variant_field_count_mismatch() {
+        mod v1 {
+            #[derive(serde::Serialize)]
+            pub enum Event {
+                Point(u8, u8),
+                Span { start: u32, end: u32 },
+            }
+        }
+        mod v2 {
+            #[allow(dead_code)]
+            #[derive(Debug, serde::Deserialize)]
+            pub enum Event {
+                Point(u8, u8, u8),
+                Span { start: u32, end: u32, step: u32 },
+            }
+        }
+
+        let bytes = crate::to_vec(&v1::Event::Point(1, 2)).unwrap();
+        assert_eq!(
+            from_slice::<v2::Event>(&bytes).unwrap_err(),
+            Error::VariantFieldCountMismatch { expected: 3 }.at(3)
+        );
+        let bytes = crate::to_vec(&v1::Event::Span { start: 1, end: 2 }).unwrap();
+        assert_eq!(
+            from_slice::<v2::Event>(&bytes).unwrap_err(),
+            Error::VariantFieldCountMismatch { expected: 3 }.at(9)
+        );
+        // running out within a field is not a field count mismatch
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        enum Named {
+            Name(u8, String),
+        }
+        let bytes = crate::to_vec(&Named::Name(1, "name".into())).unwrap();
+        assert_eq!(
+            from_slice::<Named>(&bytes[..5]).unwrap_err(),
+            Error::Eof.at(2)
+        );
+        // other errors are unchanged
+        assert_eq!(
+            from_slice::<(bool, bool)>(&[1]).unwrap_err(),
+            Error::Eof.at(1)
+        );
+    }
+
+    #[test]
+    fn test_unknown_variant() {
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        enum TestEnum {
+            A,
+            B,
+            C,
+        }
+
+        assert_eq!(from_slice::<TestEnum>(&[2]).unwrap(), TestEnum::C);
+        let err = from_slice::<TestEnum>(&[5]).unwrap_err();
+        assert_eq!(err.position(), Some(0));
+        assert_eq!(err.into_inner(), Error::UnknownVariant(5));
+    }
+
     #[test]
     fn test_struct() {
         #[derive(Debug, PartialEq, PartialOrd, Deserialize)]
@@ -745,25 +2353,1116 @@ mod tests {
             "1234567890".to_string()
         );
         assert_eq!(
-            from_slice::<String>(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 10]),
+            from_slice::<String>(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 10]).map_err(Error::into_inner),
             Err(Error::InvalidBytesEncoding(10))
         );
         assert_eq!(
-            from_slice::<String>(&[2]),
+            from_slice::<String>(&[2]).map_err(Error::into_inner),
             Err(Error::InvalidBytesEncoding(2))
         );
     }
 
     #[test]
-    #[cfg(feature = "decimal")]
-    fn test_decimal() {
-        // Notice: decimals like 100.00 will be decoding as 100.
+    fn test_string_like() {
+        use std::borrow::Cow;
+        use std::rc::Rc;
 
-        let decimals = [
-            "-inf",
-            "-123456789012345678901234",
-            "-1234567890.1234",
-            "-233.3",
+        let encoding = crate::to_vec(&"hello").unwrap();
+        assert_eq!(from_slice::<Cow<'static, str>>(&encoding).unwrap(), "hello");
+        assert_eq!(&*from_slice::<Box<str>>(&encoding).unwrap(), "hello");
+        assert_eq!(&*from_slice::<Rc<str>>(&encoding).unwrap(), "hello");
+
+        let encoding = crate::to_vec(&vec!["a", "", "bcdefghij"]).unwrap();
+        assert_eq!(
+            from_slice::<Vec<Cow<'_, str>>>(&encoding).unwrap(),
+            ["a", "", "bcdefghij"]
+        );
+
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Borrowed<'a> {
+            #[serde(borrow)]
+            s: Cow<'a, str>,
+        }
+        let encoding = crate::to_vec(&"world").unwrap();
+        assert_eq!(
+            from_slice::<Borrowed<'_>>(&encoding).unwrap(),
+            Borrowed { s: "world".into() }
+        );
+
+        // borrowed `&str` can't be supported
+        assert!(from_slice::<&str>(&encoding).is_err());
+    }
+
+    #[test]
+    fn test_sorted_map() {
+        let map: HashMap<String, Option<u8>> = HashMap::from([
+            ("b".into(), None),
+            ("a".into(), Some(1)),
+            ("".into(), Some(0)),
+        ]);
+        let mut ser = crate::Serializer::new(vec![]);
+        ser.serialize_sorted_map(&map).unwrap();
+        let bytes = ser.into_inner();
+
+        let mut de = Deserializer::new(bytes.as_slice());
+        assert_eq!(de.deserialize_sorted_map(), Ok(map));
+        assert!(!de.has_remaining());
+    }
+
+    #[test]
+    fn test_nullable_str() {
+        let values = [None, Some(""), Some("a"), Some("more than one chunk")];
+        for reverse in [false, true] {
+            let mut ser = crate::Serializer::new(vec![]);
+            ser.set_reverse(reverse);
+            for v in values {
+                ser.serialize_nullable_str(v).unwrap();
+            }
+            let bytes = ser.into_inner();
+
+            let mut de = Deserializer::new(bytes.as_slice());
+            de.set_reverse(reverse);
+            for v in values {
+                assert_eq!(de.deserialize_nullable_str(), Ok(v.map(String::from)));
+            }
+            assert!(!de.has_remaining());
+        }
+
+        let mut de = Deserializer::new(&[3][..]);
+        assert_eq!(
+            de.deserialize_nullable_str(),
+            Err(Error::InvalidBytesEncoding(3).at(0))
+        );
+        let mut de = Deserializer::new(&[2, 0xff, 0, 0, 0, 0, 0, 0, 0, 1][..]);
+        assert!(matches!(
+            de.deserialize_nullable_str().map_err(Error::into_inner),
+            Err(Error::InvalidUtf8(_))
+        ));
+    }
+
+    #[test]
+    fn test_nullable() {
+        let values = [None, Some("a".to_string()), None];
+        for (nulls_last, reverse) in [(false, false), (true, false), (false, true), (true, true)] {
+            let mut ser = crate::Serializer::new(vec![]);
+            ser.set_reverse(reverse);
+            for v in &values {
+                ser.serialize_nullable(v.as_ref(), nulls_last).unwrap();
+            }
+            let bytes = ser.into_inner();
+
+            let mut de = Deserializer::new(bytes.as_slice());
+            de.set_reverse(reverse);
+            for v in &values {
+                assert_eq!(
+                    de.deserialize_nullable::<String>(nulls_last).as_ref(),
+                    Ok(v)
+                );
+            }
+            assert!(!de.has_remaining());
+        }
+
+        // the null marker of the other order is rejected
+        let mut de = Deserializer::new(&[0xff][..]);
+        assert_eq!(
+            de.deserialize_nullable::<u8>(false),
+            Err(Error::InvalidTagEncoding(0xff).at(0))
+        );
+        let mut de = Deserializer::new(&[1][..]);
+        assert_eq!(de.deserialize_nullable::<u8>(true), Err(Error::Eof.at(1)));
+    }
+
+    #[test]
+    fn test_result() {
+        let values: [std::result::Result<u32, String>; 4] = [
+            Ok(0),
+            Ok(u32::MAX),
+            Err(String::new()),
+            Err("error".to_string()),
+        ];
+        for value in values {
+            let bytes = crate::to_vec(&value).unwrap();
+            assert_eq!(from_slice(&bytes), Ok(value));
+        }
+        assert_eq!(
+            from_slice::<std::result::Result<u32, String>>(&[2]).map_err(Error::into_inner),
+            Err(Error::UnknownVariant(2))
+        );
+    }
+
+    #[test]
+    fn test_tagged() {
+        let mut ser = crate::Serializer::new(vec![]);
+        ser.serialize_tagged(200, &("tagged", 1u8)).unwrap();
+        ser.serialize_tagged(3, &()).unwrap();
+        let bytes = ser.into_inner();
+
+        let mut de = Deserializer::new(bytes.as_slice());
+        assert_eq!(
+            de.deserialize_tagged(),
+            Ok((200, ("tagged".to_string(), 1u8)))
+        );
+        assert_eq!(de.deserialize_tagged(), Ok((3, ())));
+        assert_eq!(de.deserialize_tagged::<()>(), Err(Error::Eof.at(13)));
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let mut ser = crate::Serializer::new(vec![]);
+        ser.serialize_timestamp_millis(-1_500).unwrap();
+        ser.serialize_timestamp_micros(i64::MIN).unwrap();
+        let bytes = ser.into_inner();
+        assert_eq!(bytes, crate::to_vec(&(-1_500i64, i64::MIN)).unwrap());
+
+        let mut de = Deserializer::new(bytes.as_slice());
+        assert_eq!(de.deserialize_timestamp_millis(), Ok(-1_500));
+        assert_eq!(de.deserialize_timestamp_micros(), Ok(i64::MIN));
+        assert_eq!(de.deserialize_timestamp_millis(), Err(Error::Eof.at(16)));
+    }
+
+    #[test]
+    fn test_socket_addr() {
+        let addrs: Vec<SocketAddr> = ["10.0.0.1:80", "[::1]:443", "[fe80::1%3]:22"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        for reverse in [false, true] {
+            let mut ser = crate::Serializer::new(vec![]);
+            ser.set_reverse(reverse);
+            for &addr in &addrs {
+                ser.serialize_socket_addr(addr).unwrap();
+            }
+            let bytes = ser.into_inner();
+
+            let mut de = Deserializer::new(bytes.as_slice());
+            de.set_reverse(reverse);
+            assert_eq!(de.deserialize_socket_addr(), Ok(addrs[0]));
+            assert_eq!(de.deserialize_socket_addr(), Ok(addrs[1]));
+            // the scope id is lost
+            assert_eq!(
+                de.deserialize_socket_addr(),
+                Ok("[fe80::1]:22".parse().unwrap())
+            );
+            assert!(!de.has_remaining());
+        }
+
+        let mut de = Deserializer::new(&[2, 0, 0][..]);
+        assert_eq!(
+            de.deserialize_socket_addr(),
+            Err(Error::InvalidTagEncoding(2).at(0))
+        );
+        let mut de = Deserializer::new(&[0, 10, 0, 0, 1][..]);
+        assert_eq!(de.deserialize_socket_addr(), Err(Error::Eof.at(0)));
+    }
+
+    #[test]
+    fn test_framed() {
+        use serde::Serialize;
+
+        let mut ser = crate::Serializer::new(vec![]);
+        0xabu8.serialize(&mut ser).unwrap();
+        ser.serialize_framed(&(0x1234u32, "hello")).unwrap();
+        0xcdefu16.serialize(&mut ser).unwrap();
+        let bytes = ser.into_inner();
+
+        let mut de = Deserializer::new(bytes.as_slice());
+        assert_eq!(u8::deserialize(&mut de).unwrap(), 0xab);
+        assert_eq!(
+            de.deserialize_framed::<(u32, String)>().unwrap(),
+            (0x1234, "hello".to_string())
+        );
+        assert_eq!(u16::deserialize(&mut de).unwrap(), 0xcdef);
+        assert!(!de.has_remaining());
+
+        // the frame must be consumed exactly
+        let mut de = Deserializer::new(&bytes[1..]);
+        assert_eq!(
+            de.deserialize_framed::<u32>().unwrap_err(),
+            Error::TrailingCharacters { remaining: 10 }.at(8)
+        );
+        // a frame longer than the input
+        let mut de = Deserializer::new(&bytes[1..10]);
+        assert_eq!(
+            de.deserialize_framed::<(u32, String)>().unwrap_err(),
+            Error::Eof.at(0)
+        );
+    }
+
+    #[test]
+    fn test_fixed_bytes() {
+        for reverse in [false, true] {
+            for array in [[0u8; 16].to_vec(), [0xff; 16].to_vec(), (0..32).collect()] {
+                let mut ser = crate::Serializer::new(vec![]);
+                ser.set_reverse(reverse);
+                ser.serialize_fixed_bytes(&array).unwrap();
+                serde::Serialize::serialize(&1u8, &mut ser).unwrap();
+                let bytes = ser.into_inner();
+
+                let mut de = Deserializer::new(bytes.as_slice());
+                de.set_reverse(reverse);
+                assert_eq!(de.deserialize_fixed_bytes(array.len()).unwrap(), array);
+                assert_eq!(u8::deserialize(&mut de).unwrap(), 1);
+                assert!(!de.has_remaining());
+            }
+        }
+
+        let mut de = Deserializer::new(&[0u8; 15][..]);
+        assert_eq!(de.deserialize_fixed_bytes(16), Err(Error::Eof.at(0)));
+    }
+
+    #[test]
+    fn test_fixed_str() {
+        for reverse in [false, true] {
+            let mut ser = crate::Serializer::new(vec![]);
+            ser.set_reverse(reverse);
+            for s in ["", "key", "exactly8"] {
+                ser.serialize_fixed_str(s, 8).unwrap();
+            }
+            let bytes = ser.into_inner();
+            assert_eq!(bytes.len(), 24);
+
+            let mut de = Deserializer::new(bytes.as_slice());
+            de.set_reverse(reverse);
+            for s in ["", "key", "exactly8"] {
+                assert_eq!(de.deserialize_fixed_str(8).as_deref(), Ok(s));
+            }
+            assert!(!de.has_remaining());
+        }
+
+        // trailing zeros of the string itself are lost
+        let mut ser = crate::Serializer::new(vec![]);
+        ser.serialize_fixed_str("a\0", 4).unwrap();
+        let bytes = ser.into_inner();
+        assert_eq!(
+            Deserializer::new(bytes.as_slice())
+                .deserialize_fixed_str(4)
+                .as_deref(),
+            Ok("a")
+        );
+
+        let mut de = Deserializer::new(&[0xff, 0][..]);
+        assert!(matches!(
+            de.deserialize_fixed_str(2).map_err(Error::into_inner),
+            Err(Error::InvalidUtf8(_))
+        ));
+    }
+
+    #[test]
+    fn test_bool_slice() {
+        for reverse in [false, true] {
+            for len in [0, 1, 8, 13] {
+                let bits: Vec<bool> = (0..len).map(|i| i % 3 == 0).collect();
+                let mut ser = crate::Serializer::new(vec![]);
+                ser.set_reverse(reverse);
+                ser.serialize_bool_slice(&bits).unwrap();
+                serde::Serialize::serialize(&true, &mut ser).unwrap();
+                let bytes = ser.into_inner();
+
+                let mut de = Deserializer::new(bytes.as_slice());
+                de.set_reverse(reverse);
+                assert_eq!(de.deserialize_bool_slice(len), Ok(bits));
+                assert_eq!(bool::deserialize(&mut de), Ok(true));
+                assert!(!de.has_remaining());
+            }
+        }
+
+        let mut de = Deserializer::new(&[0xff, 0b1110_0001][..]);
+        assert_eq!(
+            de.deserialize_bool_slice(11),
+            Err(Error::InvalidBoolEncoding(0b1110_0001).at(1))
+        );
+        let mut de = Deserializer::new(&[0xff][..]);
+        assert_eq!(de.deserialize_bool_slice(9), Err(Error::Eof.at(0)));
+    }
+
+    #[test]
+    fn test_read_bytes_into() {
+        let (first, second) = ("first value, longer than a chunk", "second");
+        let bytes = crate::to_vec(&(first, second, "")).unwrap();
+
+        let mut de = Deserializer::new(bytes.as_slice());
+        let mut buf = Vec::with_capacity(64);
+        assert_eq!(de.read_bytes_into(&mut buf), Ok(first.len()));
+        assert_eq!(buf, first.as_bytes());
+        buf.clear();
+        assert_eq!(de.read_bytes_into(&mut buf), Ok(second.len()));
+        assert_eq!(buf, second.as_bytes());
+        // appends to existing content
+        assert_eq!(de.read_bytes_into(&mut buf), Ok(0));
+        assert_eq!(buf, second.as_bytes());
+        assert!(!de.has_remaining());
+
+        // matches independent decodes
+        let mut de = Deserializer::new(bytes.as_slice());
+        assert_eq!(
+            <(String, String, String)>::deserialize(&mut de),
+            Ok((first.into(), second.into(), "".into()))
+        );
+
+        // left unchanged on error
+        let mut de = Deserializer::new(&bytes[..12]);
+        let mut buf = vec![1];
+        assert_eq!(de.read_bytes_into(&mut buf), Err(Error::Eof.at(0)));
+        assert_eq!(buf, [1]);
+    }
+
+    #[test]
+    fn test_str_and_string() {
+        for s in [
+            "hello",
+            "",
+            "caf\u{e9}",
+            "\u{1f980} and more than one chunk",
+        ] {
+            let from_str = crate::to_vec(&s).unwrap();
+            let from_string = crate::to_vec(&s.to_string()).unwrap();
+            assert_eq!(from_str, from_string);
+            assert_eq!(from_slice::<String>(&from_str), Ok(s.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_seq_framing() {
+        let value = (
+            vec![1u16, 2],
+            vec![vec!["a".to_string()], vec![]],
+            vec![Some(3u8)],
+        );
+        for seq_framing in [SeqFraming::Marker, SeqFraming::LengthPrefixed] {
+            let config = Config::new().seq_framing(seq_framing);
+            let mut ser = crate::Serializer::with_config(vec![], config);
+            value.serialize(&mut ser).unwrap();
+            let bytes = ser.into_inner();
+
+            // adjacent sequences decode independently
+            let mut de = Deserializer::with_config(bytes.as_slice(), config);
+            assert_eq!(Deserialize::deserialize(&mut de), Ok(value.clone()));
+            assert!(!de.has_remaining());
+        }
+
+        let mut de = Deserializer::new(&[0, 0, 0, 2, 0, 1][..]);
+        de.set_seq_framing(SeqFraming::LengthPrefixed);
+        assert_eq!(Vec::<u16>::deserialize(&mut de), Err(Error::Eof.at(6)));
+    }
+
+    #[test]
+    fn test_seq_size_hint() {
+        /// The size hints seen before each element and at the end.
+        struct Hints(Vec<Option<usize>>);
+
+        impl<'de> Deserialize<'de> for Hints {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct HintsVisitor;
+                impl<'de> Visitor<'de> for HintsVisitor {
+                    type Value = Hints;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str("a sequence of u8")
+                    }
+
+                    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Hints, A::Error>
+                    where
+                        A: SeqAccess<'de>,
+                    {
+                        let mut hints = vec![seq.size_hint()];
+                        while seq.next_element::<u8>()?.is_some() {
+                            hints.push(seq.size_hint());
+                        }
+                        Ok(Hints(hints))
+                    }
+                }
+                deserializer.deserialize_seq(HintsVisitor)
+            }
+        }
+
+        let value = vec![1u8, 2, 3];
+        let bytes = crate::to_vec(&value).unwrap();
+        assert_eq!(from_slice::<Hints>(&bytes).unwrap().0, [None; 4]);
+
+        let config = Config::new().seq_framing(SeqFraming::LengthPrefixed);
+        let mut ser = crate::Serializer::with_config(vec![], config);
+        value.serialize(&mut ser).unwrap();
+        let bytes = ser.into_inner();
+        let mut de = Deserializer::with_config(bytes.as_slice(), config);
+        assert_eq!(
+            Hints::deserialize(&mut de).unwrap().0,
+            [Some(3), Some(2), Some(1), Some(0)]
+        );
+    }
+
+    #[test]
+    fn test_advance_past_field() {
+        let key = crate::to_vec(&(7u32, "a longer string")).unwrap();
+        let mut de = Deserializer::new(key.as_slice());
+        let group = de.advance_past_field::<u32>(&key).unwrap().unwrap();
+        let bound = de.advance_past_field::<String>(&key).unwrap().unwrap();
+        assert!(!de.has_remaining());
+
+        // the bound is past the key and its extensions, but before the next string and `u32`
+        assert!(key < bound);
+        assert!(crate::to_vec(&(7u32, "a longer string", u64::MAX)).unwrap() < bound);
+        assert!(bound < crate::to_vec(&(7u32, "a longer strinh")).unwrap());
+        assert!(bound <= group);
+        assert_eq!(group, crate::to_vec(&8u32).unwrap());
+
+        let key = crate::to_vec(&(u32::MAX, "")).unwrap();
+        let mut de = Deserializer::new(key.as_slice());
+        assert_eq!(de.advance_past_field::<u32>(&key), Ok(None));
+        assert_eq!(
+            de.advance_past_field::<String>(&key),
+            Ok(Some(vec![0xff, 0xff, 0xff, 0xff, 1]))
+        );
+
+        // the bytes consumed count from where the deserializer started
+        let mut de = Deserializer::new(&key[4..]);
+        assert_eq!(
+            de.advance_past_field::<String>(&key[4..]),
+            Ok(Some(vec![1]))
+        );
+        assert_eq!(
+            de.advance_past_field::<u8>(&key[4..])
+                .map_err(Error::into_inner),
+            Err(Error::Eof)
+        );
+    }
+
+    #[test]
+    fn test_from_slice_prefix() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct V1 {
+            id: u32,
+            time: i64,
+        }
+
+        // a key written after a `bool` column was added
+        let key = crate::to_vec(&(42u32, -5i64, true)).unwrap();
+        assert_eq!(
+            from_slice::<(u32, i64)>(&key),
+            Err(Error::TrailingCharacters { remaining: 1 }.at(12))
+        );
+        assert_eq!(from_slice_prefix::<(u32, i64)>(&key), Ok((42, -5)));
+        assert_eq!(from_slice_prefix::<V1>(&key), Ok(V1 { id: 42, time: -5 }));
+        assert_eq!(
+            from_slice_prefix::<(u32, i64, bool)>(&key),
+            from_slice(&key)
+        );
+
+        // missing columns are still an error
+        assert_eq!(
+            from_slice_prefix::<(u32, i64, bool, u8)>(&key).map_err(Error::into_inner),
+            Err(Error::Eof)
+        );
+    }
+
+    #[test]
+    fn test_checksum() {
+        let value = (42u32, "a key with a checksum".to_string(), Some(-1i8));
+        let mut ser = crate::Serializer::new(vec![]);
+        value.serialize(&mut ser).unwrap();
+        let body = ser.get_ref().clone();
+        let bytes = ser.finish_with_checksum().unwrap();
+        assert_eq!(bytes.len(), body.len() + 4);
+        assert_eq!(&bytes[..body.len()], body);
+        assert_eq!(from_slice_checked(&bytes), Ok(value.clone()));
+
+        // a single flipped bit anywhere is detected
+        for i in 0..bytes.len() {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 0x10;
+            assert!(matches!(
+                from_slice_checked::<(u32, String, Option<i8>)>(&corrupted),
+                Err(Error::ChecksumMismatch { .. })
+            ));
+        }
+
+        assert_eq!(from_slice_checked::<()>(&[0; 3]), Err(Error::Eof));
+        let mut ser = crate::Serializer::new(vec![]);
+        ().serialize(&mut ser).unwrap();
+        assert_eq!(ser.finish_with_checksum(), Ok(vec![0; 4]));
+
+        // an unterminated sequence is not checksummed
+        let mut ser = crate::Serializer::new(vec![]);
+        serde::Serializer::serialize_seq(&mut ser, None).unwrap();
+        assert_eq!(ser.finish_with_checksum(), Err(Error::IncompleteSequence));
+    }
+
+    #[test]
+    fn test_from_slice_into() {
+        type Row = (u32, Vec<u64>, String);
+        let first: Row = (
+            1,
+            (0..100).collect(),
+            "a string of more than one chunk".into(),
+        );
+        let second: Row = (2, vec![7, 8], "short".into());
+
+        let mut row = Row::default();
+        from_slice_into(&crate::to_vec(&first).unwrap(), &mut row).unwrap();
+        assert_eq!(row, first);
+        let (vec_ptr, string_ptr) = (row.1.as_ptr(), row.2.as_ptr());
+
+        // the shorter record is decoded into the existing allocations
+        from_slice_into(&crate::to_vec(&second).unwrap(), &mut row).unwrap();
+        assert_eq!(row, second);
+        assert_eq!(row.1.as_ptr(), vec_ptr);
+        assert_eq!(row.2.as_ptr(), string_ptr);
+        assert!(row.1.capacity() >= 100);
+
+        let mut bytes = crate::to_vec(&first).unwrap();
+        bytes.push(0);
+        assert_eq!(
+            from_slice_into(&bytes, &mut row),
+            Err(Error::TrailingCharacters { remaining: 1 }.at(942))
+        );
+    }
+
+    #[test]
+    fn test_scratch() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Row {
+            short: String,
+            long: String,
+            empty: String,
+        }
+
+        let rows = [
+            Row {
+                short: "a".into(),
+                long: "more than one chunk".into(),
+                empty: "".into(),
+            },
+            Row {
+                short: "b".into(),
+                long: "another long string, longer than the first".into(),
+                empty: "".into(),
+            },
+        ];
+        let mut scratch = vec![];
+        for row in &rows {
+            let bytes = crate::to_vec(row).unwrap();
+            let mut de = Deserializer::with_scratch(bytes.as_slice(), scratch);
+            assert_eq!(Row::deserialize(&mut de).as_ref(), Ok(row));
+            scratch = de.take_scratch();
+            assert_eq!(scratch, row.long.as_bytes());
+        }
+
+        // bytes go through the scratch buffer as well
+        let bytes = crate::to_vec(&"0123456789").unwrap();
+        let mut de = Deserializer::with_scratch(bytes.as_slice(), vec![]);
+        let mut seen = vec![];
+        de.with_bytes(|b| {
+            seen.extend_from_slice(b);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(seen, b"0123456789");
+        assert_eq!(de.take_scratch(), b"0123456789");
+        assert_eq!(de.take_scratch(), b"");
+
+        let bytes = [
+            1, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 9, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+        ];
+        let mut de = Deserializer::with_scratch(&bytes[..], vec![]);
+        assert!(matches!(
+            String::deserialize(&mut de).map_err(Error::into_inner),
+            Err(Error::InvalidUtf8(_))
+        ));
+    }
+
+    #[test]
+    fn test_max_alloc() {
+        let decode = |bytes: &[u8], max_alloc| {
+            let mut de = Deserializer::new(bytes);
+            de.set_max_alloc(max_alloc);
+            <(String, u8)>::deserialize(&mut de)
+        };
+        let long = "a".repeat(20);
+        let bytes = crate::to_vec(&(&long, 1u8)).unwrap();
+        assert_eq!(decode(&bytes, 20), Ok((long.clone(), 1)));
+        assert_eq!(decode(&bytes, 19), Err(err_limit(19)));
+        // rejected before reading the last chunk
+        assert_eq!(decode(&bytes[..19], 8), Err(err_limit(8)));
+        assert_eq!(decode(&bytes, 4), Err(err_limit(4)));
+
+        // markers claiming a huge array, cut off by the limit rather than the end of input
+        let mut bytes = vec![1];
+        for _ in 0..1000 {
+            bytes.extend_from_slice(&[0; 8]);
+            bytes.push(9);
+        }
+        assert_eq!(decode(&bytes, 64), Err(err_limit(64)));
+        assert_eq!(decode(&bytes, usize::MAX), Err(Error::Eof.at(0)));
+
+        // all ways of reading bytes are limited
+        let bytes = crate::to_vec(&"short").unwrap();
+        let mut de = Deserializer::new(bytes.as_slice());
+        de.set_max_alloc(4);
+        assert_eq!(de.read_borrowed_bytes(), Err(err_limit(4)));
+        let mut de = Deserializer::new(bytes.as_slice());
+        de.set_max_alloc(4);
+        assert_eq!(de.read_bytes_into(&mut vec![0; 10]), Err(err_limit(4)));
+        let mut de = Deserializer::new(bytes.as_slice());
+        de.set_max_alloc(5);
+        assert_eq!(de.read_bytes_into(&mut vec![0; 10]), Ok(5));
+
+        fn err_limit(limit: usize) -> Error {
+            Error::LengthLimitExceeded { limit }.at(0)
+        }
+    }
+
+    #[test]
+    fn test_max_depth() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Tree(Vec<Tree>);
+
+        // every `1` starts another level, which would overflow the stack without a limit
+        let err = from_slice::<Tree>(&vec![1; 1_000_000]).unwrap_err();
+        assert_eq!(err.position(), Some(128));
+        assert_eq!(err.into_inner(), Error::DepthLimitExceeded { limit: 128 });
+
+        let decode = |bytes: &[u8], max_depth| {
+            let mut de = Deserializer::new(bytes);
+            de.set_max_depth(max_depth);
+            <Vec<Option<(u8,)>>>::deserialize(&mut de).map_err(Error::into_inner)
+        };
+        let bytes = crate::to_vec(&vec![Some((1u8,))]).unwrap();
+        assert_eq!(decode(&bytes, 3), Ok(vec![Some((1,))]));
+        assert_eq!(
+            decode(&bytes, 2),
+            Err(Error::DepthLimitExceeded { limit: 2 })
+        );
+        // the depth is restored after each element
+        let bytes = crate::to_vec(&vec![Some((1u8,)); 10]).unwrap();
+        assert_eq!(decode(&bytes, 3), Ok(vec![Some((1,)); 10]));
+    }
+
+    #[test]
+    fn test_borrowed_bytes() {
+        let (short, exact, long) = ("short", "8 bytes!", "more than a chunk");
+        let bytes = crate::to_vec(&(short, exact, long, "")).unwrap();
+        let mut de = Deserializer::new(bytes.as_slice());
+        let value = de.read_borrowed_bytes().unwrap();
+        assert!(matches!(value, Cow::Borrowed(b) if b == short.as_bytes()));
+        assert_eq!(value.as_ptr(), bytes[1..].as_ptr());
+        let value = de.read_borrowed_bytes().unwrap();
+        assert!(matches!(value, Cow::Borrowed(b) if b == exact.as_bytes()));
+        let value = de.read_borrowed_bytes().unwrap();
+        assert!(matches!(value, Cow::Owned(b) if b == long.as_bytes()));
+        let value = de.read_borrowed_bytes().unwrap();
+        assert!(matches!(value, Cow::Borrowed(b) if b.is_empty()));
+        assert!(!de.has_remaining());
+
+        // reverse order is always copied
+        let bytes = crate::to_vec_desc(&short).unwrap();
+        let mut de = Deserializer::new(bytes.as_slice());
+        de.set_reverse(true);
+        let value = de.read_borrowed_bytes().unwrap();
+        assert!(matches!(value, Cow::Owned(b) if b == short.as_bytes()));
+
+        let mut bytes = crate::to_vec(&short).unwrap();
+        bytes[7] = 1;
+        let mut de = Deserializer::new(bytes.as_slice());
+        assert_eq!(
+            de.read_borrowed_bytes(),
+            Err(Error::InvalidBytesEncoding(1).at(0))
+        );
+        let mut de = Deserializer::new(&bytes[..5]);
+        assert_eq!(de.read_borrowed_bytes(), Err(Error::Eof.at(0)));
+    }
+
+    #[test]
+    fn test_deserialize_borrowed_bytes() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Row<'a> {
+            id: u32,
+            #[serde(borrow)]
+            slice: &'a [u8],
+            #[serde(borrow)]
+            bytes: &'a serde_bytes::Bytes,
+        }
+
+        // records how the bytes were passed to the visitor
+        #[derive(Debug, PartialEq)]
+        enum Visited {
+            Borrowed(Vec<u8>),
+            Copied(Vec<u8>),
+        }
+
+        impl<'de> Deserialize<'de> for Visited {
+            fn deserialize<D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> std::result::Result<Self, D::Error> {
+                struct V;
+                impl<'de> Visitor<'de> for V {
+                    type Value = Visited;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str("bytes")
+                    }
+
+                    fn visit_borrowed_bytes<E>(
+                        self,
+                        v: &'de [u8],
+                    ) -> std::result::Result<Visited, E> {
+                        Ok(Visited::Borrowed(v.to_vec()))
+                    }
+
+                    fn visit_bytes<E>(self, v: &[u8]) -> std::result::Result<Visited, E> {
+                        Ok(Visited::Copied(v.to_vec()))
+                    }
+                }
+                deserializer.deserialize_bytes(V)
+            }
+        }
+
+        // a single chunk is borrowed from the input
+        let bytes = crate::to_vec(&(1u32, serde_bytes::Bytes::new(b"short"), "")).unwrap();
+        let row: Row<'_> = crate::from_slice(&bytes).unwrap();
+        assert_eq!(row.slice, b"short");
+        assert_eq!(row.slice.as_ptr(), bytes[5..].as_ptr());
+        assert!(row.bytes.is_empty());
+        let (_, value, _): (u32, Visited, Visited) = crate::from_slice(&bytes).unwrap();
+        assert_eq!(value, Visited::Borrowed(b"short".to_vec()));
+
+        // more chunks are copied, which `&[u8]` can't hold
+        let long = serde_bytes::Bytes::new(b"more than a chunk");
+        let bytes = crate::to_vec(&(1u32, long, "")).unwrap();
+        assert!(crate::from_slice::<Row<'_>>(&bytes).is_err());
+        let (_, value, _): (u32, Visited, Visited) = crate::from_slice(&bytes).unwrap();
+        assert_eq!(value, Visited::Copied(long.to_vec()));
+
+        // so is reverse order
+        let bytes = crate::to_vec_desc(&serde_bytes::Bytes::new(b"short")).unwrap();
+        assert!(crate::from_slice_desc::<&[u8]>(&bytes).is_err());
+        let value: Visited = crate::from_slice_desc(&bytes).unwrap();
+        assert_eq!(value, Visited::Copied(b"short".to_vec()));
+    }
+
+    #[test]
+    fn test_deserialize_bytes() {
+        // a type that only looks at the bytes, like a fixed-size array
+        #[derive(Debug, PartialEq)]
+        struct Len(usize);
+
+        impl<'de> Deserialize<'de> for Len {
+            fn deserialize<D: serde::Deserializer<'de>>(
+                d: D,
+            ) -> std::result::Result<Self, D::Error> {
+                struct LenVisitor;
+                impl<'de> Visitor<'de> for LenVisitor {
+                    type Value = Len;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str("bytes")
+                    }
+
+                    fn visit_bytes<E>(self, v: &[u8]) -> std::result::Result<Len, E> {
+                        Ok(Len(v.len()))
+                    }
+                }
+                d.deserialize_bytes(LenVisitor)
+            }
+        }
+
+        for s in ["", "short", "8 bytes!", "more than a chunk"] {
+            let bytes = crate::to_vec(&s).unwrap();
+            assert_eq!(from_slice(&bytes), Ok(Len(s.len())));
+        }
+    }
+
+    #[test]
+    fn test_bytes_padding() {
+        let dirty_padding = [1, b'a', 0, 0, 0, 0, 0, 0, 0xff, 1];
+        assert_eq!(
+            from_slice::<String>(&dirty_padding).map_err(Error::into_inner),
+            Err(Error::InvalidBytesEncoding(0xff))
+        );
+        let mut de = Deserializer::new(&dirty_padding[..]);
+        assert_eq!(de.skip_bytes(), Err(Error::InvalidBytesEncoding(0xff)));
+
+        // padding is zero before flipping
+        let mut de = Deserializer::new(&[!1, !b'a', !0, !0, !0, !0, !0, !0, 0, !1][..]);
+        de.set_reverse(true);
+        assert_eq!(
+            String::deserialize(&mut de).map_err(Error::into_inner),
+            Err(Error::InvalidBytesEncoding(0xff))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_strict_decimal() {
+        let decode = |bytes: &[u8], strict| {
+            let mut de = Deserializer::new(bytes);
+            de.set_strict(strict);
+            de.deserialize_decimal().map_err(Error::into_inner)
+        };
+        let one = Decimal::Normalized(1.into());
+        assert_eq!(decode(&[0x18, 0x02], true), Ok(one));
+        // `1.00` with a redundant trailing digit pair
+        assert_eq!(decode(&[0x18, 0x03, 0x00], false), Ok(one));
+        assert_eq!(
+            decode(&[0x18, 0x03, 0x00], true),
+            Err(Error::NonCanonical("trailing zeros in decimal significand"))
+        );
+        // negative values are inverted
+        assert_eq!(
+            decode(&[0x12, !0x03, !0x00], false),
+            Ok(Decimal::Normalized((-1).into()))
+        );
+        assert!(decode(&[0x12, !0x03, !0x00], true).is_err());
+    }
+
+    #[test]
+    fn test_eof() {
+        assert_eq!(
+            from_slice::<u32>(&[0, 0]).map_err(Error::into_inner),
+            Err(Error::Eof)
+        );
+        assert_eq!(
+            from_slice::<bool>(&[]).map_err(Error::into_inner),
+            Err(Error::Eof)
+        );
+        assert_eq!(
+            from_slice::<String>(&[1, b'a', 0, 0]).map_err(Error::into_inner),
+            Err(Error::Eof)
+        );
+        assert_eq!(
+            from_slice::<Vec<u8>>(&[1, 0x12]).map_err(Error::into_inner),
+            Err(Error::Eof)
+        );
+        assert_eq!(
+            Deserializer::new(&[1, 0, 0][..]).skip_bytes(),
+            Err(Error::Eof)
+        );
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert_eq!(from_slice::<()>(&[]), Ok(()));
+        assert_eq!(from_slice::<u32>(&[]), Err(Error::Eof.at(0)));
+        assert_eq!(from_slice::<String>(&[]), Err(Error::Eof.at(0)));
+        assert_eq!(from_slice::<Option<u8>>(&[]), Err(Error::Eof.at(0)));
+        assert_eq!(
+            from_slice::<u32>(&[]).unwrap_err().to_string(),
+            "unexpected end of input (at position 0)"
+        );
+    }
+
+    #[test]
+    fn test_decode_iter() {
+        let bytes = [0, 0, 0, 1, 0, 0, 0, 2, 0xff, 0xff, 0xff, 0xff];
+        let values = decode_iter::<u32>(&bytes).collect::<Result<Vec<_>>>();
+        assert_eq!(values, Ok(vec![1, 2, u32::MAX]));
+
+        assert_eq!(decode_iter::<u32>(&[]).count(), 0);
+
+        // stop at the first error
+        let mut iter = decode_iter::<u32>(&bytes[..10]);
+        assert_eq!(iter.next(), Some(Ok(1)));
+        assert_eq!(iter.next(), Some(Ok(2)));
+        assert_eq!(iter.next(), Some(Err(Error::Eof.at(8))));
+        assert_eq!(iter.next(), None);
+
+        // values that consume nothing must not loop forever
+        let mut iter = decode_iter::<()>(&bytes);
+        assert_eq!(
+            iter.next(),
+            Some(Err(Error::TrailingCharacters { remaining: 12 }.at(0)))
+        );
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_decode_batch_lossy() {
+        type Record = (u32, bool, i16);
+        let records: Vec<Record> = (0..5).map(|i| (i, i % 2 == 0, -(i as i16))).collect();
+        let mut bytes = vec![];
+        for record in &records {
+            bytes.extend(crate::to_vec(record).unwrap());
+        }
+        let record_len = bytes.len() / records.len();
+        assert_eq!(record_len, 7);
+
+        // corrupt the bool of the third record
+        bytes[2 * record_len + 4] = 2;
+        let (values, errors) = decode_batch_lossy::<Record>(&bytes, record_len);
+        assert_eq!(values, [records[0], records[1], records[3], records[4]]);
+        assert_eq!(
+            errors,
+            [(2, Error::InvalidBoolEncoding(2).at(2 * record_len + 4))]
+        );
+
+        // a truncated last record, and a record length that leaves bytes over
+        let (values, errors) = decode_batch_lossy::<Record>(&bytes[..bytes.len() - 1], record_len);
+        assert_eq!(values.len(), 3);
+        assert_eq!(errors[1], (4, Error::Eof.at(4 * record_len + 5)));
+        let (values, errors) = decode_batch_lossy::<u32>(&bytes[..record_len], record_len);
+        assert!(values.is_empty());
+        assert_eq!(
+            errors,
+            [(0, Error::TrailingCharacters { remaining: 3 }.at(4))]
+        );
+    }
+
+    #[test]
+    fn test_owned() {
+        let value = (42u32, "hello".to_string(), Some(-1i8));
+        let key = crate::to_vec(&value).unwrap();
+
+        assert_eq!(from_owned(key.clone()), Ok(value.clone()));
+        assert_eq!(from_owned(Bytes::from(key.clone())), Ok(value.clone()));
+
+        let mut de = Deserializer::from_vec(key.clone());
+        assert_eq!(
+            <(u32, String, Option<i8>)>::deserialize(&mut de),
+            Ok(value.clone())
+        );
+        assert!(!de.has_remaining());
+        let mut de = Deserializer::from_bytes(Bytes::from(key.clone()));
+        assert_eq!(<(u32, String, Option<i8>)>::deserialize(&mut de), Ok(value));
+
+        assert_eq!(
+            from_owned::<u32>(key),
+            Err(Error::TrailingCharacters { remaining: 12 }.at(4))
+        );
+    }
+
+    #[test]
+    fn test_from_slice_desc() {
+        let value = (-7i64, "desc".to_string(), Some(1.5f32));
+        let key = crate::to_vec_desc(&value).unwrap();
+        assert_eq!(from_slice_desc(&key), Ok(value.clone()));
+        assert_ne!(from_slice::<(i64, String, Option<f32>)>(&key), Ok(value));
+        assert_eq!(
+            from_slice_desc::<i64>(&key),
+            Err(Error::TrailingCharacters { remaining: 15 }.at(8))
+        );
+    }
+
+    #[test]
+    fn test_extract_prefix() {
+        let key = |a: u32, b: &str, c: i64| crate::to_vec(&(a, b, c)).unwrap();
+        let target = key(7, "hello", -3);
+
+        let mut de = Deserializer::new(target.as_slice());
+        let prefix = de.extract_prefix(&[Skip::Fixed(4), Skip::Bytes]).unwrap();
+        assert_eq!(prefix, crate::to_vec(&(7u32, "hello")).unwrap());
+        assert_eq!(i64::deserialize(&mut de), Ok(-3));
+
+        // the prefix bounds all keys starting with (7, "hello")
+        let mut upper = prefix.to_vec();
+        *upper.last_mut().unwrap() += 1;
+        for k in [
+            key(7, "hello", i64::MIN),
+            target.clone(),
+            key(7, "hello", i64::MAX),
+        ] {
+            assert!(prefix < k.as_slice() && k < upper);
+        }
+        for k in [
+            key(7, "hell", 0),
+            key(7, "hello!", 0),
+            key(6, "z", 0),
+            key(8, "", 0),
+        ] {
+            assert!(!(prefix < k.as_slice() && k < upper));
+        }
+
+        let mut de = Deserializer::new(&target[..6]);
+        assert_eq!(
+            de.extract_prefix(&[Skip::Fixed(4), Skip::Bytes]),
+            Err(Error::Eof.at(4))
+        );
+    }
+
+    #[test]
+    fn test_from_slice_advancing() {
+        let mut encoding = crate::to_vec(&0x1234u32).unwrap();
+        encoding.extend(crate::to_vec(&"hello").unwrap());
+
+        let mut buf = encoding.as_slice();
+        assert_eq!(from_slice_advancing::<u32>(&mut buf).unwrap(), 0x1234);
+        assert_eq!(buf.len(), encoding.len() - 4);
+        assert_eq!(from_slice_advancing::<String>(&mut buf).unwrap(), "hello");
+        assert!(buf.is_empty());
+
+        // the slice is left untouched on error
+        let mut buf = &[2u8][..];
+        assert!(from_slice_advancing::<bool>(&mut buf).is_err());
+        assert_eq!(buf, [2]);
+    }
+
+    #[test]
+    fn test_peek_first() {
+        let key = crate::to_vec(&(0xdead_beefu32, "row", -42i64)).unwrap();
+        assert_eq!(peek_first::<u32>(&key), Ok(0xdead_beef));
+        assert_eq!(
+            peek_first::<(u32, String)>(&key),
+            Ok((0xdead_beef, "row".to_string()))
+        );
+        assert_eq!(peek_first::<u32>(&key[..3]), Err(Error::Eof.at(0)));
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut de = Deserializer::new(&[0x12, 0x34, 0x56][..]);
+        assert_eq!(u8::deserialize(&mut de).unwrap(), 0x12);
+        let mut checkpoint = de.clone();
+        assert_eq!(u16::deserialize(&mut de).unwrap(), 0x3456);
+        assert!(!de.has_remaining());
+
+        checkpoint.set_reverse(true);
+        assert_eq!(checkpoint.position(), 1);
+        assert_eq!(u8::deserialize(&mut checkpoint).unwrap(), !0x34);
+        assert_eq!(checkpoint.position(), 2);
+    }
+
+    #[test]
+    fn test_human_readable() {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Port(u16);
+
+        impl<'de> Deserialize<'de> for Port {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                if deserializer.is_human_readable() {
+                    let s = String::deserialize(deserializer)?;
+                    s.parse().map(Port).map_err(de::Error::custom)
+                } else {
+                    u16::deserialize(deserializer).map(Port)
+                }
+            }
+        }
+
+        assert_eq!(from_slice::<Port>(&[0x1f, 0x90]).unwrap(), Port(8080));
+        assert_eq!(
+            from_slice::<std::net::Ipv4Addr>(&[127, 0, 0, 1]).unwrap(),
+            std::net::Ipv4Addr::LOCALHOST
+        );
+
+        let encoding = crate::to_vec(&"8080").unwrap();
+        let mut de = Deserializer::new(encoding.as_slice());
+        de.set_human_readable(true);
+        assert_eq!(Port::deserialize(&mut de).unwrap(), Port(8080));
+        assert!(!de.has_remaining());
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_decimal() {
+        // Notice: decimals like 100.00 will be decoding as 100.
+
+        let decimals = [
+            "-inf",
+            "-123456789012345678901234",
+            "-1234567890.1234",
+            "-233.3",
             "-0.001",
             "0",
             "0.001",
@@ -786,6 +3485,120 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_truncated_decimal() {
+        // (decimal, length of the flag and exponent)
+        let cases = [("-1234567890.1234", 1), ("0.001", 2), ("12345", 1)];
+        for (s, header_len) in cases {
+            let decimal: Decimal = s.parse().unwrap();
+            let encoding = serialize_decimal(decimal);
+            for len in 1..encoding.len() {
+                let result = Deserializer::new(&encoding[..len]).deserialize_decimal();
+                let expected = if len < header_len {
+                    Error::Eof
+                } else {
+                    Error::TruncatedDecimal
+                };
+                assert_eq!(
+                    result.map_err(Error::into_inner),
+                    Err(expected),
+                    "{s} {len}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_finite_decimal() {
+        let decimals = [
+            "-79228162514264337593543950335",
+            "-123456789012345678901234",
+            "-233.3",
+            "-0.001",
+            "-0.0000000000000000000000000001",
+            "0",
+            "0.0000000000000000000000000001",
+            "0.001",
+            "0.01111",
+            "0.5",
+            "100",
+            "41721.900909090909090909090909",
+            "123456789012345678901234",
+            "79228162514264337593543950335",
+        ];
+        let mut last_encoding = vec![];
+        for s in decimals {
+            let decimal: rust_decimal::Decimal = s.parse().unwrap();
+            let mut serializer = crate::Serializer::new(vec![]);
+            serializer.serialize_finite_decimal(decimal).unwrap();
+            let encoding = serializer.into_inner();
+
+            let mut deserializer = Deserializer::new(encoding.as_slice());
+            assert_eq!(
+                deserializer.deserialize_finite_decimal(),
+                Ok(decimal),
+                "{s}"
+            );
+            assert!(!deserializer.has_remaining());
+            assert!(encoding > last_encoding, "{s}");
+
+            // the tag replaces the flag and exponent bytes of the full encoding
+            let full = serialize_decimal(decimal);
+            assert_eq!(
+                encoding[1..],
+                full[full.len() - encoding.len() + 1..],
+                "{s}"
+            );
+            let v = s.parse::<f64>().unwrap().abs();
+            let saved = usize::from(v != 0.0 && !(0.01..1e20).contains(&v));
+            assert_eq!(full.len() - encoding.len(), saved, "{s}");
+            last_encoding = encoding;
+        }
+
+        for tag in [0x00, 0x5f, 0xa1, 0xff] {
+            assert_eq!(
+                Deserializer::new(&[tag, 0x02][..]).deserialize_finite_decimal(),
+                Err(Error::InvalidDecimalEncoding(tag).at(0))
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_decimal_reverse() {
+        let decimals = [
+            "-inf",
+            "-123456789012345678901234",
+            "-233.3",
+            "-0.001",
+            "0",
+            "0.001",
+            "100",
+            "41721.900909090909090909090909",
+            "inf",
+            "nan",
+        ];
+        let mut last_encoding: Option<Vec<u8>> = None;
+        for s in decimals {
+            let decimal: Decimal = s.parse().unwrap();
+            let mut serializer = crate::Serializer::new(vec![]);
+            serializer.set_reverse(true);
+            serializer.serialize_decimal(decimal).unwrap();
+            let encoding = serializer.into_inner();
+
+            let mut deserializer = Deserializer::new(encoding.as_slice());
+            deserializer.set_reverse(true);
+            assert_eq!(deserializer.deserialize_decimal(), Ok(decimal), "{s}");
+            assert!(!deserializer.has_remaining());
+            if let Some(last) = last_encoding {
+                assert!(encoding < last, "{s}");
+            }
+            last_encoding = Some(encoding);
+        }
+    }
+
     #[cfg(feature = "decimal")]
     fn serialize_decimal(decimal: impl Into<Decimal>) -> Vec<u8> {
         let mut serializer = crate::Serializer::new(vec![]);
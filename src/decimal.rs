@@ -12,10 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{Deserializer, Error, Serializer};
+use crate::{Deserializer, Serializer};
 use std::fmt::Display;
 use std::str::FromStr;
 
+/// The tag of zero in the finite decimal encoding.
+pub(crate) const FINITE_DECIMAL_ZERO: u8 = 0x80;
+/// The tag of a positive finite decimal with exponent 0; the exponent is added to it.
+pub(crate) const FINITE_DECIMAL_POS_BASE: u8 = 0x90;
+/// The tag of a negative finite decimal with exponent 0; the exponent is subtracted from it.
+pub(crate) const FINITE_DECIMAL_NEG_BASE: u8 = 0x70;
+
 /// An extended decimal number with `NaN`, `-Inf` and `Inf`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
@@ -34,6 +41,97 @@ impl Decimal {
     /// A constant representing 0.
     pub const ZERO: Self = Decimal::Normalized(rust_decimal::Decimal::ZERO);
 
+    /// Create a decimal of `mantissa * 10^-scale`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mantissa` does not fit in 96 bits or `scale` is greater than 28.
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::Decimal;
+    ///
+    /// assert_eq!(Decimal::from_mantissa_scale(-1234, 2).to_string(), "-12.34");
+    /// ```
+    pub fn from_mantissa_scale(mantissa: i128, scale: u32) -> Self {
+        Decimal::Normalized(rust_decimal::Decimal::from_i128_with_scale(mantissa, scale))
+    }
+
+    /// Convert a float to a decimal.
+    ///
+    /// `NaN` and the infinities map to the special variants. Returns `None` if a finite value is
+    /// out of the range of `rust_decimal::Decimal`.
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::Decimal;
+    ///
+    /// assert_eq!(Decimal::from_f64(0.5), Some("0.5".parse().unwrap()));
+    /// assert_eq!(Decimal::from_f64(f64::NEG_INFINITY), Some(Decimal::NegInf));
+    /// assert_eq!(Decimal::from_f64(1e30), None);
+    /// ```
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if value.is_nan() {
+            Some(Decimal::NaN)
+        } else if value == f64::INFINITY {
+            Some(Decimal::Inf)
+        } else if value == f64::NEG_INFINITY {
+            Some(Decimal::NegInf)
+        } else {
+            rust_decimal::Decimal::try_from(value)
+                .ok()
+                .map(Decimal::Normalized)
+        }
+    }
+
+    /// Return the number of bytes [`Serializer::serialize_decimal`] writes for the decimal,
+    /// without encoding it.
+    ///
+    /// Special values and zero take 1 byte. Other values take 1 or 2 bytes for the exponent, and
+    /// 1 byte per pair of significant digits.
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::Decimal;
+    ///
+    /// let d: Decimal = "-123.45".parse().unwrap();
+    /// assert_eq!(d.encoded_len(), d.to_vec().unwrap().len());
+    /// assert_eq!(Decimal::NaN.encoded_len(), 1);
+    /// ```
+    pub fn encoded_len(&self) -> usize {
+        let d = match self {
+            Decimal::Normalized(d) if !d.is_zero() => d,
+            _ => return 1,
+        };
+        let mut mantissa = d.mantissa().unsigned_abs();
+        let prec = mantissa.ilog10() as i32 + 1;
+        let e10 = prec - d.scale() as i32;
+        // the significand is in base 100, with a leading zero digit for an odd exponent
+        let e100 = if e10 >= 0 { (e10 + 1) / 2 } else { e10 / 2 };
+        let mut digits = if e10 == 2 * e100 { prec } else { prec + 1 };
+        while mantissa.is_multiple_of(10) {
+            mantissa /= 10;
+            digits -= 1;
+        }
+        let exponent_len = if (0..=10).contains(&e100) { 1 } else { 2 };
+        exponent_len + (digits as usize).div_ceil(2)
+    }
+
+    /// Parse a decimal like [`from_str`](std::str::FromStr::from_str), returning the error of
+    /// this crate.
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::{Decimal, Error};
+    ///
+    /// assert_eq!(Decimal::parse("-1.5"), Ok(Decimal::from_mantissa_scale(-15, 1)));
+    /// assert_eq!(Decimal::parse("Inf"), Ok(Decimal::Inf));
+    /// assert!(matches!(Decimal::parse("1.2.3"), Err(Error::Decimal(_))));
+    /// ```
+    pub fn parse(s: &str) -> crate::Result<Self> {
+        Ok(s.parse()?)
+    }
+
     /// Serialize the decimal into a vector.
     pub fn to_vec(&self) -> crate::Result<Vec<u8>> {
         let mut serializer = Serializer::new(vec![]);
@@ -45,11 +143,8 @@ impl Decimal {
     pub fn from_slice(bytes: &[u8]) -> crate::Result<Self> {
         let mut deserializer = Deserializer::new(bytes);
         let t = deserializer.deserialize_decimal()?;
-        if !deserializer.has_remaining() {
-            Ok(t)
-        } else {
-            Err(Error::TrailingCharacters)
-        }
+        deserializer.end()?;
+        Ok(t)
     }
 }
 
@@ -82,3 +177,257 @@ impl Display for Decimal {
         }
     }
 }
+
+/// (De)serialize a bare `rust_decimal::Decimal` with the memcomparable decimal encoding.
+///
+/// Use it with `#[serde(with = "memcomparable::rust_decimal_serde")]` on a field. The encoding is
+/// the same as [`Serializer::serialize_decimal`] with [`Decimal::Normalized`]. Since a
+/// `rust_decimal::Decimal` is always finite, `NaN` and the infinities can not be decoded.
+///
+/// The bytes are passed through serde as a tuple of `u8`s, so this only makes sense with the
+/// memcomparable `Serializer` and `Deserializer`.
+///
+/// # Example
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Order {
+///     #[serde(with = "memcomparable::rust_decimal_serde")]
+///     amount: rust_decimal::Decimal,
+/// }
+///
+/// let order = Order { amount: "12.34".parse().unwrap() };
+/// let bytes = memcomparable::to_vec(&order).unwrap();
+/// assert_eq!(memcomparable::from_slice::<Order>(&bytes).unwrap(), order);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+pub mod rust_decimal_serde {
+    use std::fmt;
+
+    use serde::de::{self, SeqAccess, Visitor};
+    use serde::ser::{self, SerializeTuple};
+
+    use super::Decimal;
+    use crate::Deserializer;
+
+    /// The maximum length of an encoded decimal: the flag, the exponent and at most 15 bytes of
+    /// significand for the 29 digits of a `rust_decimal::Decimal`.
+    const MAX_ENCODED_LEN: usize = 17;
+
+    /// Serialize a decimal as [`Decimal::Normalized`].
+    pub fn serialize<S>(value: &rust_decimal::Decimal, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let bytes = Decimal::Normalized(*value)
+            .to_vec()
+            .map_err(ser::Error::custom)?;
+        let mut tuple = serializer.serialize_tuple(bytes.len())?;
+        for b in &bytes {
+            tuple.serialize_element(b)?;
+        }
+        tuple.end()
+    }
+
+    /// Deserialize a decimal serialized by [`serialize`].
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<rust_decimal::Decimal, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(MAX_ENCODED_LEN, DecimalVisitor)
+    }
+
+    struct DecimalVisitor;
+
+    impl<'de> Visitor<'de> for DecimalVisitor {
+        type Value = rust_decimal::Decimal;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a memcomparable decimal")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = Vec::with_capacity(MAX_ENCODED_LEN);
+            let mut next = |bytes: &mut Vec<u8>| -> Result<u8, A::Error> {
+                let b = seq
+                    .next_element::<u8>()?
+                    .ok_or_else(|| de::Error::custom("unexpected end of decimal"))?;
+                bytes.push(b);
+                Ok(b)
+            };
+
+            // read until the end of the encoding, then decode it as a whole
+            let flag = next(&mut bytes)?;
+            match flag {
+                0x07 | 0x23 | 0x24 => return Err(de::Error::custom("decimal is not finite")),
+                0x08 | 0x14 | 0x16 | 0x22 => {
+                    next(&mut bytes)?;
+                }
+                _ => {}
+            }
+            if flag != 0x15 {
+                let neg = flag < 0x15;
+                loop {
+                    let b = next(&mut bytes)?;
+                    let b = if neg { !b } else { b };
+                    if b & 1 == 0 {
+                        break;
+                    }
+                }
+            }
+
+            match Deserializer::new(bytes.as_slice()).deserialize_decimal() {
+                Ok(Decimal::Normalized(d)) => Ok(d),
+                Ok(_) => Err(de::Error::custom("decimal is not finite")),
+                Err(e) => Err(de::Error::custom(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::Error;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Order {
+        #[serde(with = "rust_decimal_serde")]
+        amount: rust_decimal::Decimal,
+    }
+
+    #[test]
+    fn test_constructors() {
+        let cases = [
+            (0, 0, "0"),
+            (1, 28, "0.0000000000000000000000000001"),
+            (-12345, 3, "-12.345"),
+            (
+                79228162514264337593543950335,
+                0,
+                "79228162514264337593543950335",
+            ),
+        ];
+        for (mantissa, scale, s) in cases {
+            let d = Decimal::from_mantissa_scale(mantissa, scale);
+            assert_eq!(d, s.parse().unwrap());
+            assert_eq!(Decimal::from_slice(&d.to_vec().unwrap()), Ok(d));
+        }
+
+        for (f, s) in [
+            (0.0, "0"),
+            (-1.5, "-1.5"),
+            (0.1, "0.1"),
+            (1e20, "100000000000000000000"),
+        ] {
+            let d = Decimal::from_f64(f).unwrap();
+            assert_eq!(d, s.parse().unwrap());
+            assert_eq!(Decimal::from_slice(&d.to_vec().unwrap()), Ok(d));
+        }
+        assert_eq!(Decimal::from_f64(f64::NAN), Some(Decimal::NaN));
+        assert_eq!(Decimal::from_f64(f64::INFINITY), Some(Decimal::Inf));
+        assert_eq!(Decimal::from_f64(-1e30), None);
+
+        // ordering agrees between the two constructors
+        let a = Decimal::from_mantissa_scale(-15, 1);
+        let b = Decimal::from_f64(-1.4).unwrap();
+        assert!(a < b && a.to_vec().unwrap() < b.to_vec().unwrap());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Decimal::parse("0.001"),
+            "0.001".parse::<Decimal>().map_err(Error::from)
+        );
+        assert_eq!(Decimal::parse("nan"), Ok(Decimal::NaN));
+        assert_eq!(Decimal::parse("-inf"), Ok(Decimal::NegInf));
+        for invalid in ["", "abc", "1.2.3", "1e", "99999999999999999999999999999999"] {
+            let err = invalid.parse::<Decimal>().unwrap_err();
+            assert_eq!(
+                Decimal::parse(invalid),
+                Err(Error::Decimal(err.to_string()))
+            );
+        }
+
+        // `?` converts into the crate error
+        fn parse_pair(a: &str, b: &str) -> crate::Result<(Decimal, Decimal)> {
+            Ok((a.parse()?, b.parse()?))
+        }
+        assert!(parse_pair("1", "2").is_ok());
+        assert!(matches!(parse_pair("1", "x"), Err(Error::Decimal(_))));
+    }
+
+    #[test]
+    fn test_encoded_len() {
+        let mut decimals = vec![Decimal::NaN, Decimal::Inf, Decimal::NegInf, Decimal::ZERO];
+        for s in [
+            "1",
+            "-1",
+            "10",
+            "100",
+            "0.1",
+            "0.01",
+            "0.001",
+            "-0.0123",
+            "111.11",
+            "1e20",
+            "1e21",
+            "-1e22",
+            "1e-20",
+            "12345678901234567890",
+            "0.0000000000000000000000000001",
+        ] {
+            decimals.push(s.parse().unwrap());
+        }
+        decimals.push(rust_decimal::Decimal::MAX.into());
+        decimals.push(rust_decimal::Decimal::MIN.into());
+        for _ in 0..1000 {
+            decimals.push(Decimal::Normalized(rand::random()));
+        }
+        for d in decimals {
+            assert_eq!(d.encoded_len(), d.to_vec().unwrap().len(), "{d}");
+        }
+    }
+
+    #[test]
+    fn test_rust_decimal_serde() {
+        let mut amounts: Vec<rust_decimal::Decimal> = ["-1e10", "-12.34", "0", "0.001", "1", "100"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        amounts.push(rust_decimal::Decimal::MAX);
+        amounts.push(rust_decimal::Decimal::MIN);
+        for _ in 0..100 {
+            amounts.push(rand::random());
+        }
+
+        for &amount in &amounts {
+            let order = Order { amount };
+            let bytes = crate::to_vec(&order).unwrap();
+            assert_eq!(bytes, Decimal::Normalized(amount).to_vec().unwrap());
+            assert_eq!(crate::from_slice::<Order>(&bytes).unwrap(), order);
+
+            let mut ser = Serializer::new(vec![]);
+            ser.set_reverse(true);
+            order.serialize(&mut ser).unwrap();
+            let bytes = ser.into_inner();
+            let mut de = Deserializer::new(bytes.as_slice());
+            de.set_reverse(true);
+            assert_eq!(Order::deserialize(&mut de).unwrap(), order);
+        }
+
+        amounts.sort();
+        let encodings: Vec<_> = amounts
+            .iter()
+            .map(|&amount| crate::to_vec(&Order { amount }).unwrap())
+            .collect();
+        assert!(encodings.windows(2).all(|w| w[0] <= w[1]));
+
+        // infinities can not be represented
+        assert!(crate::from_slice::<Order>(&Decimal::Inf.to_vec().unwrap()).is_err());
+    }
+}
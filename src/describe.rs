@@ -0,0 +1,153 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A best-effort tokenizer for inspecting encoded keys without their schema.
+
+use crate::bytes_format::{CHUNK_SIZE, CHUNK_UNIT_SIZE};
+
+/// A piece of an encoded key recognized by [`describe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// A non-empty byte array or string, with its decoded content.
+    Bytes(Vec<u8>),
+    /// A `0` or `1`: an option tag, a sequence marker, a bool, an empty byte array, or a byte of
+    /// a number.
+    Marker(u8),
+    /// Bytes with no recognizable framing, such as the bytes of numbers.
+    Raw(&'a [u8]),
+}
+
+/// Split an encoded key into tokens, for debugging.
+///
+/// The format is not self-describing, so this can only guess: a `1` followed by well-formed
+/// chunk groups is taken as a byte array, any other `0` or `1` as a marker, and runs of other
+/// bytes as raw data. Numbers containing `0` or `1` bytes are split into markers, and a
+/// sequence of small numbers may be mistaken for a byte array. Only ascending order is
+/// recognized.
+///
+/// # Example
+/// ```
+/// use memcomparable::Token;
+///
+/// let key = memcomparable::to_vec(&("key", Some(7u8))).unwrap();
+/// assert_eq!(
+///     memcomparable::describe(&key),
+///     [Token::Bytes(b"key".to_vec()), Token::Marker(1), Token::Raw(&[7])]
+/// );
+/// ```
+pub fn describe(bytes: &[u8]) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut pos = 0;
+    let mut raw_start = None;
+    while pos < bytes.len() {
+        let b = bytes[pos];
+        if b > 1 {
+            raw_start.get_or_insert(pos);
+            pos += 1;
+            continue;
+        }
+        if let Some(start) = raw_start.take() {
+            tokens.push(Token::Raw(&bytes[start..pos]));
+        }
+        match (b, parse_bytes(&bytes[pos..])) {
+            (1, Some((data, len))) => {
+                tokens.push(Token::Bytes(data));
+                pos += len;
+            }
+            _ => {
+                tokens.push(Token::Marker(b));
+                pos += 1;
+            }
+        }
+    }
+    if let Some(start) = raw_start {
+        tokens.push(Token::Raw(&bytes[start..]));
+    }
+    tokens
+}
+
+/// Parse a non-empty byte array at the start of `input`, returning its content and length.
+fn parse_bytes(input: &[u8]) -> Option<(Vec<u8>, usize)> {
+    let mut data = vec![];
+    let mut pos = 1;
+    loop {
+        let unit = input.get(pos..pos + CHUNK_UNIT_SIZE)?;
+        pos += CHUNK_UNIT_SIZE;
+        match unit[CHUNK_SIZE] {
+            len @ 1..=8 => {
+                let (chunk, padding) = unit[..CHUNK_SIZE].split_at(len as usize);
+                if padding.iter().any(|&b| b != 0) {
+                    return None;
+                }
+                data.extend_from_slice(chunk);
+                return Some((data, pos));
+            }
+            9 => data.extend_from_slice(&unit[..CHUNK_SIZE]),
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let value = ("a longer string".to_string(), vec![1u8, 2], Some(7u8));
+        let bytes = crate::to_vec(&value).unwrap();
+        assert_eq!(
+            describe(&bytes),
+            [
+                Token::Bytes(b"a longer string".to_vec()),
+                // the sequence: a marker and a value for each element, then the end
+                Token::Marker(1),
+                Token::Marker(1),
+                Token::Marker(1),
+                Token::Raw(&[2]),
+                Token::Marker(0),
+                // the option
+                Token::Marker(1),
+                Token::Raw(&[7]),
+            ]
+        );
+
+        let bytes = crate::to_vec(&("", None::<u8>, 0x1234u16)).unwrap();
+        assert_eq!(
+            describe(&bytes),
+            [
+                Token::Marker(0),
+                Token::Marker(0),
+                Token::Raw(&[0x12, 0x34])
+            ]
+        );
+
+        // bad padding or a truncated group is not a byte array
+        assert_eq!(
+            describe(&[1, b'a', 5, 0, 0, 0, 0, 0, 0, 1]),
+            [
+                Token::Marker(1),
+                Token::Raw(&[b'a', 5]),
+                Token::Marker(0),
+                Token::Marker(0),
+                Token::Marker(0),
+                Token::Marker(0),
+                Token::Marker(0),
+                Token::Marker(0),
+                Token::Marker(1),
+            ]
+        );
+        assert_eq!(describe(&[]), []);
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// A dynamically typed scalar, for columns whose values may have different types.
+///
+/// Serialized by [`Serializer::serialize_dynamic`] as a type tag followed by the value, so values
+/// of different types sort in the order of the variants below, and values of the same type by
+/// value.
+///
+/// [`Serializer::serialize_dynamic`]: crate::Serializer::serialize_dynamic
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynValue {
+    /// A null, sorting before any other value.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A signed integer.
+    Int(i64),
+    /// A float, ordered as described in the [crate documentation](crate#format).
+    Float(f64),
+    /// A UTF-8 string.
+    String(String),
+    /// A byte array.
+    Bytes(Vec<u8>),
+}
+
+impl DynValue {
+    pub(crate) const NULL: u8 = 0;
+    pub(crate) const BOOL: u8 = 1;
+    pub(crate) const INT: u8 = 2;
+    pub(crate) const FLOAT: u8 = 3;
+    pub(crate) const STRING: u8 = 4;
+    pub(crate) const BYTES: u8 = 5;
+
+    /// Return the type tag written before the value.
+    pub fn tag(&self) -> u8 {
+        match self {
+            DynValue::Null => Self::NULL,
+            DynValue::Bool(_) => Self::BOOL,
+            DynValue::Int(_) => Self::INT,
+            DynValue::Float(_) => Self::FLOAT,
+            DynValue::String(_) => Self::STRING,
+            DynValue::Bytes(_) => Self::BYTES,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Deserializer, Serializer};
+
+    fn serialize(v: &DynValue) -> Vec<u8> {
+        let mut ser = Serializer::new(vec![]);
+        ser.serialize_dynamic(v).unwrap();
+        ser.into_inner()
+    }
+
+    #[test]
+    fn test_dynamic() {
+        // sorted by type first, then by value
+        let values = [
+            DynValue::Null,
+            DynValue::Bool(false),
+            DynValue::Bool(true),
+            DynValue::Int(i64::MIN),
+            DynValue::Int(-1),
+            DynValue::Int(100),
+            DynValue::Float(f64::NEG_INFINITY),
+            DynValue::Float(-1.5),
+            DynValue::Float(0.5),
+            DynValue::Float(f64::NAN),
+            DynValue::String("".into()),
+            DynValue::String("abc".into()),
+            DynValue::Bytes(vec![]),
+            DynValue::Bytes(vec![0; 20]),
+        ];
+        let encodings: Vec<_> = values.iter().map(serialize).collect();
+        for (v, e) in values.iter().zip(&encodings) {
+            assert_eq!(e[0], v.tag());
+            let mut de = Deserializer::new(e.as_slice());
+            let decoded = de.deserialize_dynamic().unwrap();
+            match v {
+                DynValue::Float(f) if f.is_nan() => {
+                    assert!(matches!(decoded, DynValue::Float(d) if d.is_nan()))
+                }
+                _ => assert_eq!(&decoded, v),
+            }
+            assert!(!de.has_remaining());
+        }
+        assert!(encodings.windows(2).all(|w| w[0] < w[1]));
+
+        // an int sorts before any float and a float before any string, whatever their values
+        assert!(serialize(&DynValue::Int(i64::MAX)) < serialize(&DynValue::Float(f64::MIN)));
+        assert!(serialize(&DynValue::Float(f64::MAX)) < serialize(&DynValue::String("".into())));
+
+        // reverse order flips the order of types as well
+        let mut ser = Serializer::new(vec![]);
+        ser.set_reverse(true);
+        ser.serialize_dynamic(&DynValue::Int(1)).unwrap();
+        let bytes = ser.into_inner();
+        let mut de = Deserializer::new(bytes.as_slice());
+        de.set_reverse(true);
+        assert_eq!(de.deserialize_dynamic().unwrap(), DynValue::Int(1));
+
+        let mut de = Deserializer::new(&[6u8][..]);
+        assert_eq!(
+            de.deserialize_dynamic(),
+            Err(crate::Error::InvalidTagEncoding(6).at(0))
+        );
+    }
+
+    #[test]
+    fn test_row_with_nulls() {
+        let rows = [
+            vec![None; 10],
+            vec![
+                Some(DynValue::Int(1)),
+                None,
+                Some(DynValue::String("a".into())),
+            ],
+            vec![
+                None,
+                Some(DynValue::Null),
+                Some(DynValue::Float(2.5)),
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(DynValue::Bytes(vec![1, 2, 3])),
+            ],
+            vec![],
+        ];
+        for row in &rows {
+            let mut ser = Serializer::new(vec![]);
+            ser.serialize_row_with_nulls(row).unwrap();
+            let bytes = ser.into_inner();
+            let mut de = Deserializer::new(bytes.as_slice());
+            assert_eq!(&de.deserialize_row_with_nulls(row.len()).unwrap(), row);
+            assert!(!de.has_remaining());
+        }
+
+        // nulls take a bit each: 2 bytes of bitmap, then a tag and 8 bytes for the int
+        let mut row = vec![None; 16];
+        row[15] = Some(DynValue::Int(0));
+        let mut ser = Serializer::new(vec![]);
+        ser.serialize_row_with_nulls(&row).unwrap();
+        assert_eq!(ser.into_inner().len(), 2 + 1 + 8);
+
+        // rows are grouped by their bitmaps first
+        let encode = |row: &[Option<DynValue>]| {
+            let mut ser = Serializer::new(vec![]);
+            ser.serialize_row_with_nulls(row).unwrap();
+            ser.into_inner()
+        };
+        let a = encode(&[Some(DynValue::Int(2)), None]);
+        let b = encode(&[Some(DynValue::Int(1)), Some(DynValue::Int(3))]);
+        assert!(a < b);
+    }
+}
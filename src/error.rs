@@ -28,24 +28,110 @@ pub enum Error {
     Message(String),
     #[error("unexpected end of input")]
     Eof,
+    /// A type or method that has no memcomparable encoding.
+    ///
+    /// In particular maps are rejected with `"map"`, because they do not iterate in a defined
+    /// order in general. Encode the entries as a `Vec<(K, V)>` sorted by key instead, e.g. with
+    /// [`Serializer::serialize_entries`](crate::Serializer::serialize_entries).
     #[error("unsupported type: {0}")]
     NotSupported(&'static str),
     #[error("invalid bool encoding: {0}")]
     InvalidBoolEncoding(u8),
-    #[error("invalid char encoding: {0}")]
-    InvalidCharEncoding(u32),
+    #[error("invalid char code point: {0:#x} ({})", char_code_point_issue(*.0))]
+    InvalidCharCodePoint(u32),
     #[error("invalid tag encoding: {0}")]
     InvalidTagEncoding(usize),
+    #[error("unknown variant index: {0}")]
+    UnknownVariant(u32),
+    #[error("input ended within the fields of an enum variant, which has {expected} fields")]
+    VariantFieldCountMismatch { expected: usize },
     #[error("invalid sequence encoding: {0}")]
     InvalidSeqEncoding(u8),
+    #[error("incomplete sequence: serialization finished before the sequence was ended")]
+    IncompleteSequence,
+    #[error("sequence length mismatch: {actual} elements for a length prefix of {expected}")]
+    SeqLengthMismatch { expected: usize, actual: usize },
     #[error("invalid UTF8: {0}")]
     InvalidUtf8(#[from] std::string::FromUtf8Error),
     #[error("invalid bytes encoding: {0}")]
     InvalidBytesEncoding(u8),
+    #[error("byte array exceeds the length limit of {limit} bytes")]
+    LengthLimitExceeded { limit: usize },
+    #[error("nesting exceeds the depth limit of {limit}")]
+    DepthLimitExceeded { limit: usize },
     #[error("invalid decimal encoding: {0}")]
     InvalidDecimalEncoding(u8),
-    #[error("trailing characters")]
-    TrailingCharacters,
+    #[error("truncated decimal: the significand continues past the end of input")]
+    TruncatedDecimal,
+    #[error("NaN is not allowed")]
+    NanNotAllowed,
+    #[error("non-canonical encoding: {0}")]
+    NonCanonical(&'static str),
+    #[error("trailing characters: {remaining} bytes left")]
+    TrailingCharacters { remaining: usize },
+    #[error("checksum mismatch: expected {expected:#010x}, computed {actual:#010x}")]
+    ChecksumMismatch { expected: u32, actual: u32 },
+    #[error("buffer too small: {needed} bytes needed")]
+    BufferTooSmall { needed: usize },
+    #[error("I/O error: {0}")]
+    Io(String),
+    #[error("decimal error: {0}")]
+    Decimal(String),
+    #[error("{source} (at position {position})")]
+    At { position: usize, source: Box<Error> },
+}
+
+impl Error {
+    /// Attach the byte position at which the error occurred.
+    ///
+    /// If the error already carries a position, the innermost one is kept.
+    pub(crate) fn at(self, position: usize) -> Self {
+        match self {
+            Error::At { .. } => self,
+            _ => Error::At {
+                position,
+                source: Box::new(self),
+            },
+        }
+    }
+
+    /// Return the byte position in the input at which the error occurred, if known.
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            Error::At { position, .. } => Some(*position),
+            _ => None,
+        }
+    }
+
+    /// Strip the position information and return the underlying error.
+    pub fn into_inner(self) -> Error {
+        match self {
+            Error::At { source, .. } => *source,
+            _ => self,
+        }
+    }
+}
+
+/// Describe why `u` is not a valid `char`.
+fn char_code_point_issue(u: u32) -> &'static str {
+    if (0xD800..=0xDFFF).contains(&u) {
+        "surrogate"
+    } else {
+        "out of range"
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e.to_string())
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl From<rust_decimal::Error> for Error {
+    fn from(e: rust_decimal::Error) -> Self {
+        Error::Decimal(e.to_string())
+    }
 }
 
 impl ser::Error for Error {
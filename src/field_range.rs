@@ -0,0 +1,354 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::Range;
+
+use serde::ser::{self, Error as _, Impossible, Serialize, SerializeTuple};
+
+/// Serialize a contiguous range of the fields of a struct or tuple, as a tuple of those fields.
+///
+/// This encodes a subset of the columns of a row as a key, e.g. for a covering index, without
+/// defining another type. The value must serialize as a struct, a tuple struct or a tuple, and
+/// the range must be within its fields.
+///
+/// # Example
+/// ```
+/// use memcomparable::FieldRange;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Row {
+///     id: u32,
+///     name: String,
+///     age: u8,
+///     city: String,
+/// }
+///
+/// let row = Row {
+///     id: 1,
+///     name: "alice".into(),
+///     age: 30,
+///     city: "paris".into(),
+/// };
+/// assert_eq!(
+///     memcomparable::to_vec(&FieldRange::new(&row, 1..3)).unwrap(),
+///     memcomparable::to_vec(&("alice", 30u8)).unwrap()
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct FieldRange<'a, T: ?Sized> {
+    value: &'a T,
+    range: Range<usize>,
+}
+
+impl<'a, T: ?Sized> FieldRange<'a, T> {
+    /// Select the fields of `value` in `range`.
+    pub fn new(value: &'a T, range: Range<usize>) -> Self {
+        FieldRange { value, range }
+    }
+}
+
+impl<T: ?Sized + Serialize> Serialize for FieldRange<'_, T> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(FieldFilter {
+            inner: serializer,
+            range: self.range.clone(),
+            lens: None,
+        })
+    }
+}
+
+/// Return the encoded width of each field of a struct or tuple, which must all have a fixed
+/// width.
+pub(crate) fn field_lens<T: ?Sized + Serialize>(value: &T) -> crate::Result<Vec<usize>> {
+    let mut lens = vec![];
+    let mut empty = crate::Serializer::new(vec![]);
+    value.serialize(FieldFilter {
+        inner: &mut empty,
+        range: 0..0,
+        lens: Some(&mut lens),
+    })?;
+    Ok(lens)
+}
+
+/// A serializer that forwards the fields in `range` of a compound value to `inner` as a tuple.
+///
+/// If `lens` is set, the fixed width of every field is pushed to it.
+struct FieldFilter<'a, S> {
+    inner: S,
+    range: Range<usize>,
+    lens: Option<&'a mut Vec<usize>>,
+}
+
+impl<'a, S: ser::Serializer> FieldFilter<'a, S> {
+    fn begin(self, len: usize) -> Result<Fields<'a, S::SerializeTuple>, S::Error> {
+        if self.range.start > self.range.end || self.range.end > len {
+            return Err(S::Error::custom(format_args!(
+                "field range {:?} is out of bounds of {len} fields",
+                self.range
+            )));
+        }
+        Ok(Fields {
+            tuple: self.inner.serialize_tuple(self.range.len())?,
+            range: self.range,
+            index: 0,
+            lens: self.lens,
+        })
+    }
+}
+
+/// The fields of a compound value, of which those in `range` are written to `tuple`.
+struct Fields<'a, T> {
+    tuple: T,
+    range: Range<usize>,
+    index: usize,
+    lens: Option<&'a mut Vec<usize>>,
+}
+
+impl<T: SerializeTuple> Fields<'_, T> {
+    fn field<V: ?Sized + Serialize>(&mut self, value: &V) -> Result<(), T::Error> {
+        if let Some(lens) = &mut self.lens {
+            let len = crate::width::fixed_width(value).map_err(T::Error::custom)?;
+            lens.push(len);
+        }
+        if self.range.contains(&self.index) {
+            self.tuple.serialize_element(value)?;
+        }
+        self.index += 1;
+        Ok(())
+    }
+}
+
+impl<T: SerializeTuple> ser::SerializeStruct for Fields<'_, T> {
+    type Ok = T::Ok;
+    type Error = T::Error;
+
+    fn serialize_field<V: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &V,
+    ) -> Result<(), T::Error> {
+        self.field(value)
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), T::Error> {
+        // skipped fields are not counted, as they are absent from the encoding of the struct
+        Ok(())
+    }
+
+    fn end(self) -> Result<T::Ok, T::Error> {
+        self.tuple.end()
+    }
+}
+
+impl<T: SerializeTuple> ser::SerializeTuple for Fields<'_, T> {
+    type Ok = T::Ok;
+    type Error = T::Error;
+
+    fn serialize_element<V: ?Sized + Serialize>(&mut self, value: &V) -> Result<(), T::Error> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<T::Ok, T::Error> {
+        self.tuple.end()
+    }
+}
+
+impl<T: SerializeTuple> ser::SerializeTupleStruct for Fields<'_, T> {
+    type Ok = T::Ok;
+    type Error = T::Error;
+
+    fn serialize_field<V: ?Sized + Serialize>(&mut self, value: &V) -> Result<(), T::Error> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<T::Ok, T::Error> {
+        self.tuple.end()
+    }
+}
+
+/// The error for a type that has no fields to select.
+const NOT_COMPOUND: &str = "FieldRange only supports structs, tuple structs and tuples";
+
+/// Reject a type that has no fields to select.
+macro_rules! not_compound {
+    ($($method:ident($($arg:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret, S::Error> {
+                Err(S::Error::custom(NOT_COMPOUND))
+            }
+        )*
+    };
+}
+
+impl<'a, S: ser::Serializer> ser::Serializer for FieldFilter<'a, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Impossible<S::Ok, S::Error>;
+    type SerializeTuple = Fields<'a, S::SerializeTuple>;
+    type SerializeTupleStruct = Fields<'a, S::SerializeTuple>;
+    type SerializeTupleVariant = Impossible<S::Ok, S::Error>;
+    type SerializeMap = Impossible<S::Ok, S::Error>;
+    type SerializeStruct = Fields<'a, S::SerializeTuple>;
+    type SerializeStructVariant = Impossible<S::Ok, S::Error>;
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        self.begin(len)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        self.begin(len)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        self.begin(len)
+    }
+
+    not_compound! {
+        serialize_bool(bool) -> S::Ok;
+        serialize_i8(i8) -> S::Ok;
+        serialize_i16(i16) -> S::Ok;
+        serialize_i32(i32) -> S::Ok;
+        serialize_i64(i64) -> S::Ok;
+        serialize_i128(i128) -> S::Ok;
+        serialize_u8(u8) -> S::Ok;
+        serialize_u16(u16) -> S::Ok;
+        serialize_u32(u32) -> S::Ok;
+        serialize_u64(u64) -> S::Ok;
+        serialize_u128(u128) -> S::Ok;
+        serialize_f32(f32) -> S::Ok;
+        serialize_f64(f64) -> S::Ok;
+        serialize_char(char) -> S::Ok;
+        serialize_str(&str) -> S::Ok;
+        serialize_bytes(&[u8]) -> S::Ok;
+        serialize_none() -> S::Ok;
+        serialize_unit() -> S::Ok;
+        serialize_unit_struct(&'static str) -> S::Ok;
+        serialize_unit_variant(&'static str, u32, &'static str) -> S::Ok;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<S::Ok, S::Error> {
+        Err(S::Error::custom(NOT_COMPOUND))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        // look through newtypes, which are encoded as their inner value
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        Err(S::Error::custom(NOT_COMPOUND))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+    use crate::{to_vec, Error};
+
+    #[derive(Serialize)]
+    struct Row {
+        id: u32,
+        name: String,
+        score: Option<i64>,
+        #[serde(skip)]
+        _cache: u8,
+        tags: Vec<String>,
+        active: bool,
+    }
+
+    #[test]
+    fn test_field_range() {
+        let row = Row {
+            id: 7,
+            name: "name".into(),
+            score: Some(-3),
+            _cache: 0,
+            tags: vec!["a".into(), "b".into()],
+            active: true,
+        };
+        assert_eq!(
+            to_vec(&FieldRange::new(&row, 1..4)).unwrap(),
+            to_vec(&("name", Some(-3i64), vec!["a", "b"])).unwrap()
+        );
+        assert_eq!(
+            to_vec(&FieldRange::new(&row, 0..5)).unwrap(),
+            to_vec(&row).unwrap()
+        );
+        assert_eq!(to_vec(&FieldRange::new(&row, 2..2)).unwrap(), []);
+
+        // tuples, and tuple structs through a newtype
+        #[derive(Serialize)]
+        struct Pair(u8, &'static str);
+        #[derive(Serialize)]
+        struct Wrapper(Pair);
+        let tuple = (1u8, 2u16, "three", 4u64);
+        assert_eq!(
+            to_vec(&FieldRange::new(&tuple, 2..4)).unwrap(),
+            to_vec(&("three", 4u64)).unwrap()
+        );
+        assert_eq!(
+            to_vec(&FieldRange::new(&Wrapper(Pair(1, "x")), 1..2)).unwrap(),
+            to_vec(&"x").unwrap()
+        );
+
+        // the range can also be encoded in reverse order
+        let mut ser = crate::Serializer::new(vec![]);
+        ser.set_reverse(true);
+        FieldRange::new(&row, 0..2).serialize(&mut ser).unwrap();
+        assert_eq!(
+            ser.into_inner(),
+            crate::to_vec_desc(&(7u32, "name")).unwrap()
+        );
+
+        assert!(matches!(
+            to_vec(&FieldRange::new(&row, 3..6)),
+            Err(Error::Message(_))
+        ));
+        assert!(matches!(
+            to_vec(&FieldRange::new(&1u8, 0..1)),
+            Err(Error::Message(_))
+        ));
+    }
+}
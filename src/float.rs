@@ -0,0 +1,173 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Where NaN sorts relative to other floats.
+///
+/// All NaNs are encoded the same, so they are equal to each other under either policy. Both
+/// encodings decode as NaN, so the `Deserializer` needs no matching setting.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NanOrder {
+    /// NaN sorts after `+inf`.
+    #[default]
+    High,
+    /// NaN sorts before `-inf`.
+    Low,
+}
+
+macro_rules! ordered_float {
+    ($name:ident, $float:ty, $bits:ty, $key:ident) => {
+        /// Return the bits that `Serializer` writes for a float.
+        ///
+        /// All NaNs are normalized to a positive NaN that sorts after `+inf`, and `-0.0` is
+        /// normalized to `0.0`. Other values, including subnormals, keep their bits, with the sign
+        /// bit set for positive values and all bits flipped for negative ones.
+        pub(crate) fn $key(mut v: $float) -> $bits {
+            if v.is_nan() {
+                v = <$float>::NAN; // normalize pos/neg NaN
+            } else if v == 0.0 {
+                v = 0.0; // normalize pos/neg zero
+            }
+            let u = v.to_bits();
+            if v.is_sign_positive() {
+                u | (1 << (<$bits>::BITS - 1))
+            } else {
+                !u
+            }
+        }
+
+        #[doc = concat!("An `", stringify!($float), "` whose `Ord` matches its memcomparable encoding.")]
+        ///
+        /// All NaNs are equal to each other and greater than `+inf`, and `-0.0` is equal to
+        /// `0.0`. It (de)serializes exactly like the inner float.
+        #[derive(Debug, Default, Clone, Copy)]
+        pub struct $name(pub $float);
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                $key(self.0) == $key(other.0)
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                $key(self.0).cmp(&$key(other.0))
+            }
+        }
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                $key(self.0).hash(state);
+            }
+        }
+
+        impl From<$float> for $name {
+            fn from(v: $float) -> Self {
+                Self(v)
+            }
+        }
+
+        impl From<$name> for $float {
+            fn from(v: $name) -> Self {
+                v.0
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.0.serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                <$float>::deserialize(deserializer).map(Self)
+            }
+        }
+    };
+}
+
+ordered_float!(OrderedF32, f32, u32, f32_key);
+ordered_float!(OrderedF64, f64, u64, f64_key);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_slice, to_vec};
+
+    #[test]
+    fn test_ordered_f64() {
+        let values = [
+            f64::NEG_INFINITY,
+            f64::MIN,
+            -1.0,
+            -f64::MIN_POSITIVE,
+            -0.0,
+            0.0,
+            f64::MIN_POSITIVE,
+            1.0,
+            f64::MAX,
+            f64::INFINITY,
+            f64::NAN,
+            -f64::NAN,
+        ];
+        for &a in &values {
+            for &b in &values {
+                let (oa, ob) = (OrderedF64(a), OrderedF64(b));
+                let (ea, eb) = (to_vec(&oa).unwrap(), to_vec(&ob).unwrap());
+                assert_eq!(oa.cmp(&ob), ea.cmp(&eb), "{a} vs {b}");
+                assert_eq!(oa == ob, ea == eb, "{a} vs {b}");
+            }
+        }
+        assert_eq!(OrderedF64(-0.0), OrderedF64(0.0));
+        assert_eq!(OrderedF64(f64::NAN), OrderedF64(-f64::NAN));
+        assert!(OrderedF64(f64::NAN) > OrderedF64(f64::INFINITY));
+
+        let bytes = to_vec(&OrderedF64(1.5)).unwrap();
+        assert_eq!(bytes, to_vec(&1.5f64).unwrap());
+        assert_eq!(from_slice::<OrderedF64>(&bytes), Ok(OrderedF64(1.5)));
+    }
+
+    #[test]
+    fn test_ordered_f32() {
+        let values = [
+            f32::NEG_INFINITY,
+            -1.0,
+            -0.0,
+            0.0,
+            1.0,
+            f32::INFINITY,
+            f32::NAN,
+        ];
+        for &a in &values {
+            for &b in &values {
+                let (oa, ob) = (OrderedF32(a), OrderedF32(b));
+                let (ea, eb) = (to_vec(&oa).unwrap(), to_vec(&ob).unwrap());
+                assert_eq!(oa.cmp(&ob), ea.cmp(&eb), "{a} vs {b}");
+            }
+        }
+    }
+}
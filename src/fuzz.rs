@@ -0,0 +1,144 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{Deserializer, Serializer};
+
+/// Serialize `value` in both orders and check that it deserializes back to an equal value.
+///
+/// Returns `false` if any step fails or the decoded value differs.
+///
+/// # Example
+/// ```
+/// assert!(memcomparable::fuzz_roundtrip((1u32, "hello".to_string())));
+/// ```
+pub fn fuzz_roundtrip<T>(value: T) -> bool
+where
+    T: Serialize + DeserializeOwned + PartialEq,
+{
+    [false, true].into_iter().all(|reverse| {
+        let mut ser = Serializer::new(vec![]);
+        ser.set_reverse(reverse);
+        if value.serialize(&mut ser).is_err() {
+            return false;
+        }
+        let bytes = ser.into_inner();
+
+        let mut de = Deserializer::new(bytes.as_slice());
+        de.set_reverse(reverse);
+        match T::deserialize(&mut de) {
+            Ok(v) => v == value && !de.has_remaining(),
+            Err(_) => false,
+        }
+    })
+}
+
+/// Deserialize arbitrary `bytes` as `T` in both orders, discarding the result.
+///
+/// Decoding untrusted input must report an error rather than panic, so any panic from this
+/// function is a bug.
+///
+/// # Example
+/// ```
+/// memcomparable::fuzz_decode_doesnt_panic::<(u64, String)>(&[0x01, 0x02, 0x03]);
+/// ```
+pub fn fuzz_decode_doesnt_panic<T: DeserializeOwned>(bytes: &[u8]) {
+    for reverse in [false, true] {
+        let mut de = Deserializer::new(bytes);
+        de.set_reverse(reverse);
+        let _ = T::deserialize(&mut de);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Unit,
+        Circle(f64),
+        Rect { w: u32, h: u32 },
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        assert!(fuzz_roundtrip(()));
+        assert!(fuzz_roundtrip(true));
+        assert!(fuzz_roundtrip(-12345i64));
+        assert!(fuzz_roundtrip(u128::MAX));
+        assert!(fuzz_roundtrip(-0.5f32));
+        assert!(fuzz_roundtrip('字'));
+        assert!(fuzz_roundtrip("hello, world".to_string()));
+        assert!(fuzz_roundtrip(Some(vec![1u16, 2, 3])));
+        assert!(fuzz_roundtrip((Shape::Unit, Shape::Circle(1.5))));
+        assert!(fuzz_roundtrip(Shape::Rect { w: 3, h: 4 }));
+    }
+
+    #[test]
+    fn test_decode_doesnt_panic() {
+        fn check(bytes: &[u8]) {
+            fuzz_decode_doesnt_panic::<bool>(bytes);
+            fuzz_decode_doesnt_panic::<i128>(bytes);
+            fuzz_decode_doesnt_panic::<f64>(bytes);
+            fuzz_decode_doesnt_panic::<char>(bytes);
+            fuzz_decode_doesnt_panic::<String>(bytes);
+            fuzz_decode_doesnt_panic::<Vec<u8>>(bytes);
+            fuzz_decode_doesnt_panic::<Option<Vec<String>>>(bytes);
+            fuzz_decode_doesnt_panic::<(u8, Shape)>(bytes);
+        }
+
+        // truncations of valid encodings
+        let value = (1u8, Shape::Rect { w: 3, h: 4 });
+        let bytes = crate::to_vec(&value).unwrap();
+        for i in 0..bytes.len() {
+            check(&bytes[..i]);
+        }
+        let bytes = crate::to_vec(&Some(vec!["a".repeat(20)])).unwrap();
+        for i in 0..bytes.len() {
+            check(&bytes[..i]);
+        }
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let len = rng.gen_range(0..32);
+            let bytes: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            check(&bytes);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_decimal_doesnt_panic() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let len = rng.gen_range(0..32);
+            let mut bytes: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            if let Some(b) = bytes.first_mut() {
+                *b = rng.gen_range(0x07..=0x24);
+            }
+            let _ = Deserializer::new(bytes.as_slice()).deserialize_decimal();
+        }
+        // a mantissa too long for any decimal
+        let bytes = [[0x22, 0x00].as_slice(), &[0xff; 40], &[0x00]].concat();
+        assert!(Deserializer::new(bytes.as_slice())
+            .deserialize_decimal()
+            .is_err());
+    }
+}
@@ -0,0 +1,335 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers working on encoded keys as plain bytes.
+
+use serde::Serialize;
+
+use crate::ser::to_vec;
+
+/// Return the length of the longest common prefix of two encoded keys.
+///
+/// # Example
+/// ```
+/// let a = memcomparable::to_vec(&(1u8, "apple")).unwrap();
+/// let b = memcomparable::to_vec(&(1u8, "apricot")).unwrap();
+/// assert_eq!(memcomparable::common_prefix_len(&a, &b), 4);
+/// ```
+pub fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// Return the first `n` bytes of an encoded key, or the whole key if it is shorter.
+///
+/// Encodings are compared field by field, so keys whose first columns are equal share the
+/// encoding of those columns as a prefix. Taking a prefix of the encoded length of the leading
+/// columns, e.g. from [`column_boundary_offsets`], thus groups keys by those columns without
+/// hashing, e.g. for sharding or prefix bloom filters.
+///
+/// # Example
+/// ```
+/// let a = memcomparable::to_vec(&(7u32, "a")).unwrap();
+/// let b = memcomparable::to_vec(&(7u32, "b")).unwrap();
+/// assert_eq!(memcomparable::key_prefix(&a, 4), memcomparable::key_prefix(&b, 4));
+/// assert_eq!(memcomparable::key_prefix(&a, 100), a);
+/// ```
+pub fn key_prefix(key: &[u8], n: usize) -> &[u8] {
+    &key[..n.min(key.len())]
+}
+
+/// Return the smallest key that is greater than every key starting with `prefix`.
+///
+/// This is the exclusive upper bound of a scan over all keys with the prefix: the last byte
+/// that is not `0xff` is incremented and the rest is dropped. Returns `None` if the prefix
+/// consists of `0xff` bytes only, in which case there is no upper bound.
+///
+/// # Example
+/// ```
+/// assert_eq!(memcomparable::successor(&[1, 2, 0xff]), Some(vec![1, 3]));
+/// assert_eq!(memcomparable::successor(&[0xff]), None);
+/// ```
+pub fn successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let last = prefix.iter().rposition(|&b| b != 0xff)?;
+    let mut key = prefix[..=last].to_vec();
+    key[last] += 1;
+    Some(key)
+}
+
+/// Return the byte offset of each field of a fixed-width struct or tuple type, followed by the
+/// total length.
+///
+/// The offsets are measured on `T::default()`, and hold for all values of `T` because each field
+/// must have a fixed width, such as numbers, bools, chars, and arrays or tuples of them. Returns
+/// an error if a field is a string, byte array, option, sequence, map or enum, whose width
+/// depends on the value.
+///
+/// # Example
+/// ```
+/// #[derive(Default, serde::Serialize)]
+/// struct Row {
+///     id: u32,
+///     time: i64,
+///     deleted: bool,
+/// }
+///
+/// let offsets = memcomparable::column_boundary_offsets::<Row>().unwrap();
+/// assert_eq!(offsets, [0, 4, 12, 13]);
+/// ```
+pub fn column_boundary_offsets<T: Default + Serialize>() -> crate::Result<Vec<usize>> {
+    let lens = crate::field_range::field_lens(&T::default())?;
+    let mut offsets = Vec::with_capacity(lens.len() + 1);
+    offsets.push(0);
+    for len in lens {
+        offsets.push(offsets.last().unwrap() + len);
+    }
+    Ok(offsets)
+}
+
+/// Convert an ascending key to a descending one or vice versa, in place.
+///
+/// Reverse order flips every bit written, so this gives the same bytes as serializing the value
+/// again with [`set_reverse`](crate::Serializer::set_reverse) toggled, without decoding it. This
+/// holds for all encodings, including decimals: the negated significand bytes of a negative
+/// decimal are part of its ascending encoding and are flipped along with everything else.
+///
+/// # Example
+/// ```
+/// let mut key = memcomparable::to_vec(&(1u8, "a")).unwrap();
+/// memcomparable::flip_in_place(&mut key);
+/// assert_eq!(key, memcomparable::to_vec_desc(&(1u8, "a")).unwrap());
+/// ```
+pub fn flip_in_place(key: &mut [u8]) {
+    for b in key {
+        *b = !*b;
+    }
+}
+
+/// Return the descending key of an ascending one or vice versa.
+///
+/// See [`flip_in_place`] for details.
+pub fn flip(key: &[u8]) -> Vec<u8> {
+    key.iter().map(|b| !b).collect()
+}
+
+/// A type with a smallest and a largest value in the encoding order.
+pub trait BoundedKey: Serialize + Sized {
+    /// The value with the smallest encoding.
+    const MIN: Self;
+    /// The value with the largest encoding.
+    const MAX: Self;
+}
+
+macro_rules! impl_bounded_key {
+    ($($ty:ty: $min:expr, $max:expr;)*) => {
+        $(
+            impl BoundedKey for $ty {
+                const MIN: Self = $min;
+                const MAX: Self = $max;
+            }
+        )*
+    };
+}
+
+impl_bounded_key! {
+    bool: false, true;
+    char: '\0', char::MAX;
+    u8: u8::MIN, u8::MAX;
+    u16: u16::MIN, u16::MAX;
+    u32: u32::MIN, u32::MAX;
+    u64: u64::MIN, u64::MAX;
+    u128: u128::MIN, u128::MAX;
+    i8: i8::MIN, i8::MAX;
+    i16: i16::MIN, i16::MAX;
+    i32: i32::MIN, i32::MAX;
+    i64: i64::MIN, i64::MAX;
+    i128: i128::MIN, i128::MAX;
+    // NaN sorts above `+inf` by default
+    f32: f32::NEG_INFINITY, f32::NAN;
+    f64: f64::NEG_INFINITY, f64::NAN;
+}
+
+/// Return the smallest encoded key of a type, e.g. `[0; 4]` for `u32`.
+///
+/// This is the ascending encoding with the default [`Config`](crate::Config). It is useful as
+/// the lower bound of a full-range scan.
+///
+/// # Example
+/// ```
+/// assert_eq!(memcomparable::min_key::<u32>(), [0; 4]);
+/// assert_eq!(memcomparable::max_key::<u32>(), [0xff; 4]);
+/// ```
+pub fn min_key<T: BoundedKey>() -> Vec<u8> {
+    to_vec(&T::MIN).expect("bounded keys are always serializable")
+}
+
+/// Return the largest encoded key of a type, e.g. `[0xff; 4]` for `u32`.
+///
+/// This is the ascending encoding with the default [`Config`](crate::Config). It is useful as
+/// the upper bound of a full-range scan.
+pub fn max_key<T: BoundedKey>() -> Vec<u8> {
+    to_vec(&T::MAX).expect("bounded keys are always serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::to_vec_desc;
+
+    #[test]
+    fn test_common_prefix_len() {
+        let a = [1, 2, 3, 4];
+        assert_eq!(common_prefix_len(&a, &a), 4);
+        assert_eq!(common_prefix_len(&a, &[5, 6]), 0);
+        assert_eq!(common_prefix_len(&a, &[1, 2]), 2);
+        assert_eq!(common_prefix_len(&[1, 2], &a), 2);
+        assert_eq!(common_prefix_len(&a, &[1, 2, 4, 4]), 2);
+        assert_eq!(common_prefix_len(&[], &a), 0);
+    }
+
+    #[test]
+    fn test_successor() {
+        assert_eq!(successor(&[]), None);
+        assert_eq!(successor(&[0xff, 0xff]), None);
+        assert_eq!(successor(&[0]), Some(vec![1]));
+        assert_eq!(successor(&[1, 0xfe]), Some(vec![1, 0xff]));
+        assert_eq!(successor(&[1, 0xff, 0xff]), Some(vec![2]));
+
+        let prefix = to_vec(&(1u8, "ab")).unwrap();
+        let next = successor(&prefix).unwrap();
+        for key in [
+            to_vec(&(1u8, "ab", 0u8)).unwrap(),
+            to_vec(&(1u8, "ab", u64::MAX)).unwrap(),
+        ] {
+            assert!(prefix < key && key < next);
+        }
+        assert!(next <= to_vec(&(1u8, "ac")).unwrap());
+    }
+
+    #[test]
+    fn test_column_boundary_offsets() {
+        #[derive(Default, Serialize)]
+        struct Row {
+            id: u32,
+            time: i64,
+            deleted: bool,
+        }
+        let offsets = column_boundary_offsets::<Row>().unwrap();
+        assert_eq!(offsets, [0, 4, 12, 13]);
+        assert_eq!(
+            column_boundary_offsets::<(u32, i64, bool)>().unwrap(),
+            offsets
+        );
+        assert_eq!(
+            column_boundary_offsets::<(char, [u16; 3], (u8, f64))>().unwrap(),
+            [0, 4, 10, 19]
+        );
+        // measured as encoded, e.g. an `Ipv4Addr` as its 4 octets rather than a string
+        #[derive(Serialize)]
+        struct Peer {
+            id: u32,
+            addr: std::net::Ipv4Addr,
+            seen: i64,
+        }
+        impl Default for Peer {
+            fn default() -> Self {
+                Peer {
+                    id: 0,
+                    addr: std::net::Ipv4Addr::UNSPECIFIED,
+                    seen: 0,
+                }
+            }
+        }
+        assert_eq!(column_boundary_offsets::<Peer>().unwrap(), [0, 4, 8, 16]);
+        assert!(column_boundary_offsets::<u32>().is_err());
+        assert_eq!(
+            column_boundary_offsets::<(u32, String, i64)>(),
+            Err(crate::Error::Message("str has no fixed width".into()))
+        );
+        assert!(column_boundary_offsets::<(u32, Option<i64>)>().is_err());
+        assert!(column_boundary_offsets::<(u32, (u8, Vec<u8>))>().is_err());
+
+        // keys sharing the first column share the prefix up to its boundary
+        let a = to_vec(&(7u32, -1i64, true)).unwrap();
+        let b = to_vec(&(7u32, 5i64, false)).unwrap();
+        let c = to_vec(&(8u32, -1i64, true)).unwrap();
+        assert_eq!(key_prefix(&a, offsets[1]), key_prefix(&b, offsets[1]));
+        assert_ne!(key_prefix(&a, offsets[1]), key_prefix(&c, offsets[1]));
+        assert_eq!(key_prefix(&a, offsets[3]), a);
+        assert_eq!(key_prefix(&a, 100), a);
+        assert_eq!(key_prefix(&a, 0), []);
+    }
+
+    #[test]
+    fn test_min_max_key() {
+        assert_eq!(min_key::<u32>(), [0; 4]);
+        assert_eq!(max_key::<u32>(), [0xff; 4]);
+        assert_eq!(min_key::<i8>(), [0]);
+        assert_eq!(max_key::<i8>(), [0xff]);
+        assert_eq!(min_key::<bool>(), [0]);
+        assert_eq!(max_key::<bool>(), [1]);
+        assert_eq!(min_key::<char>(), [0; 4]);
+        assert_eq!(min_key::<f64>(), to_vec(&f64::NEG_INFINITY).unwrap());
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let key = to_vec(&rng.gen::<i32>()).unwrap();
+            assert!(min_key::<i32>() <= key && key <= max_key::<i32>());
+            let key = to_vec(&rng.gen::<u128>()).unwrap();
+            assert!(min_key::<u128>() <= key && key <= max_key::<u128>());
+            let key = to_vec(&rng.gen::<char>()).unwrap();
+            assert!(min_key::<char>() <= key && key <= max_key::<char>());
+            let key = to_vec(&f64::from_bits(rng.gen())).unwrap();
+            assert!(min_key::<f64>() <= key && key <= max_key::<f64>());
+            let key = to_vec(&f32::from_bits(rng.gen())).unwrap();
+            assert!(min_key::<f32>() <= key && key <= max_key::<f32>());
+        }
+    }
+
+    #[test]
+    fn test_flip() {
+        fn check(value: &impl Serialize) {
+            let asc = to_vec(value).unwrap();
+            let desc = to_vec_desc(value).unwrap();
+            assert_eq!(flip(&asc), desc);
+            assert_eq!(flip(&desc), asc);
+            let mut key = asc;
+            flip_in_place(&mut key);
+            assert_eq!(key, desc);
+        }
+
+        check(&(-1i32, 2u64, 'x', true));
+        check(&("a string longer than a chunk", vec![Some(1.5f64), None]));
+        check(&Some(Some(())));
+        check(&std::net::Ipv6Addr::LOCALHOST);
+    }
+
+    #[test]
+    #[cfg(feature = "decimal")]
+    fn test_flip_decimal() {
+        use crate::{Decimal, Serializer};
+
+        for s in ["-inf", "-233.3", "-0.001", "0", "12345", "inf", "nan"] {
+            let decimal: Decimal = s.parse().unwrap();
+            let encode = |reverse| {
+                let mut ser = Serializer::new(vec![]);
+                ser.set_reverse(reverse);
+                ser.serialize_decimal(decimal).unwrap();
+                ser.into_inner()
+            };
+            assert_eq!(flip(&encode(false)), encode(true), "{s}");
+        }
+    }
+}
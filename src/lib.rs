@@ -36,6 +36,19 @@
 //! - `decimal`: Enable (de)serialization for [`Decimal`] type.
 //!     - [`Serializer::serialize_decimal`]
 //!     - [`Deserializer::deserialize_decimal`]
+//!     - [`Serializer::serialize_finite_decimal`]
+//!     - [`Deserializer::deserialize_finite_decimal`]
+//!     - [`rust_decimal_serde`]
+//! - `chrono`: Enable (de)serialization for signed `chrono::TimeDelta`.
+//!     - [`Serializer::serialize_time_delta`]
+//!     - [`Deserializer::deserialize_time_delta`]
+//! - `tokio`: Enable serializing into a `tokio::io::AsyncWrite`.
+//!     - [`to_async_writer`]
+//! - `fuzzing`: Enable oracles for fuzz targets.
+//!     - [`fuzz_roundtrip`]
+//!     - [`fuzz_decode_doesnt_panic`]
+//! - `test-util`: Enable helpers for testing the order of encodings.
+//!     - [`test_util::assert_order`]
 //!
 //! # Format
 //!
@@ -43,6 +56,7 @@
 //!
 //! | Type                                          | Length (bytes)       |
 //! | --------------------------------------------- | -------------------- |
+//! | `()`/unit struct/`PhantomData<T>`             | 0                    |
 //! | `bool`                                        | 1                    |
 //! | `char`                                        | 4                    |
 //! | `i8`/`i16`/`i32`/`i64`/`u8`/`u16`/`u32`/`u64` | 1/2/4/8              |
@@ -55,19 +69,148 @@
 //! | `struct { a: T1, b: T2, .. }`                 | sum(len(Ti))         |
 //! | `enum { V1, V2, .. }`                         | 1 + len(Vi)          |
 //!
+//! Integers are written in big-endian byte order, so that the most significant byte is compared
+//! first. Signed integers have their sign bit flipped to sort negative values before positive
+//! ones.
+//!
+//! Newtype structs and the integer wrappers `Wrapping<T>` and `Saturating<T>` are encoded as
+//! their inner value.
+//!
+//! `None` is encoded as `0` and `Some(v)` as `1` followed by `v`, so `None` sorts before any
+//! `Some`. Nested options stay unambiguous: `Option<Option<T>>` orders as
+//! `None < Some(None) < Some(Some(_))`.
+//!
+//! Serde treats `Vec<u8>` and `&[u8]` as sequences of `u8`, taking 2 bytes per element. Annotate
+//! such fields with `#[serde(with = "serde_bytes")]`, or with the [`as_bytes`] module of this
+//! crate, to use the more compact `bytes` encoding, which orders the same way.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Plain {
+//!     data: Vec<u8>,
+//! }
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Compact {
+//!     #[serde(with = "serde_bytes")]
+//!     data: Vec<u8>,
+//! }
+//!
+//! let data: Vec<u8> = (0..20).collect();
+//! let plain = memcomparable::to_vec(&Plain { data: data.clone() }).unwrap();
+//! let compact = memcomparable::to_vec(&Compact { data: data.clone() }).unwrap();
+//! assert_eq!(plain.len(), 20 * 2 + 1);
+//! assert_eq!(compact.len(), 1 + 3 * 9);
+//! assert_eq!(compact, memcomparable::to_vec(&serde_bytes::Bytes::new(&data)).unwrap());
+//!
+//! let value: Compact = memcomparable::from_slice(&compact).unwrap();
+//! assert_eq!(value, Compact { data });
+//! ```
+//!
+//! Floats are ordered by value, with `-0.0` encoded the same as `0.0`, and subnormals in place
+//! next to zero. All NaNs are encoded as the same NaN, which sorts above `+inf`, or below `-inf`
+//! with [`NanOrder::Low`]. Thus `-0.0` decodes as `0.0`, and the sign and payload of NaNs are
+//! lost.
+//!
+//! No encoding is a prefix of another one of the same type, so comparing encodings compares the
+//! values field by field. This also means that variable-length fields such as strings and sequences
+//! may appear in any position of a tuple or struct, not only the last. In particular, sequences
+//! compare lexicographically: an empty sequence sorts before any other, and a sequence before its
+//! extensions. Reverse order flips every bit, which exactly reverses the order of such prefix-free
+//! encodings.
+//!
+//! Unit types such as `PhantomData<T>` are encoded as nothing, so adding such a marker field to a
+//! struct does not change its encoding. A sequence of units still has its markers, so a
+//! `Vec<()>` of length `n` is encoded as `n` ones and a zero, a unary counter that sorts by length.
+//!
+//! Fixed-size arrays `[T; N]` are serialized as tuples, so an array of integers is packed as `N`
+//! big-endian values, and compares element by element like the array itself.
+//!
+//! Tuples and structs add no framing around their fields. A key with more columns than serde
+//! supports in a tuple (16) can be written as nested tuples, or by serializing the columns in
+//! turn with the same [`Serializer`]. Both produce the same bytes as a flat tuple would:
+//!
+//! ```
+//! use memcomparable::{Deserializer, Serializer};
+//! use serde::{Deserialize, Serialize};
+//!
+//! let key = ((1u8, 2u16, 3u32, 4u64), ("five", 6i8));
+//!
+//! let mut ser = Serializer::new(vec![]);
+//! 1u8.serialize(&mut ser).unwrap();
+//! 2u16.serialize(&mut ser).unwrap();
+//! (3u32, 4u64).serialize(&mut ser).unwrap();
+//! ("five", 6i8).serialize(&mut ser).unwrap();
+//! let bytes = ser.into_inner();
+//! assert_eq!(bytes, memcomparable::to_vec(&key).unwrap());
+//!
+//! let mut de = Deserializer::new(bytes.as_slice());
+//! let first = <(u8, u16, u32, u64)>::deserialize(&mut de).unwrap();
+//! let rest = <(String, i8)>::deserialize(&mut de).unwrap();
+//! assert_eq!(first, (1, 2, 3, 4));
+//! assert_eq!(rest, ("five".to_string(), 6));
+//! ```
+//!
 //! **WARN: The format is not guaranteed to be stable in minor version change, e.g. 0.1 -> 0.2.**
 
 #![deny(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+pub mod as_bytes;
+pub mod bytes_format;
+mod config;
+mod crc;
+mod cursor;
 mod de;
 #[cfg(feature = "decimal")]
 mod decimal;
+mod describe;
+mod dynamic;
 mod error;
+mod field_range;
+mod float;
+#[cfg(feature = "fuzzing")]
+mod fuzz;
+mod key;
+mod pool;
 mod ser;
+mod tee;
+#[cfg(feature = "test-util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
+pub mod test_util;
+mod time;
+mod width;
 
-pub use de::{from_slice, Deserializer};
+pub use config::{Config, SeqFraming};
+pub use cursor::FieldCursor;
+pub use de::{
+    decode_batch_lossy, decode_iter, from_owned, from_slice, from_slice_advancing,
+    from_slice_checked, from_slice_desc, from_slice_into, from_slice_prefix, peek_first,
+    Deserializer, Input, Skip,
+};
 #[cfg(feature = "decimal")]
-pub use decimal::Decimal;
+pub use decimal::{rust_decimal_serde, Decimal};
+pub use describe::{describe, Token};
+pub use dynamic::DynValue;
 pub use error::{Error, Result};
-pub use ser::{to_vec, Serializer};
+pub use field_range::FieldRange;
+pub use float::{NanOrder, OrderedF32, OrderedF64};
+#[cfg(feature = "fuzzing")]
+pub use fuzz::{fuzz_decode_doesnt_panic, fuzz_roundtrip};
+pub use key::{
+    column_boundary_offsets, common_prefix_len, flip, flip_in_place, key_prefix, max_key, min_key,
+    successor, BoundedKey,
+};
+pub use pool::{PooledSerializer, SerializerPool};
+#[cfg(feature = "tokio")]
+pub use ser::to_async_writer;
+pub use ser::{
+    can_serialize, compare_by_encoding, to_bytes, to_slice, to_vec, to_vec_batch, to_vec_desc,
+    Serializer,
+};
+pub use tee::TeeBuf;
+pub use time::{
+    system_time_from_micros, system_time_from_millis, system_time_to_micros, system_time_to_millis,
+};
@@ -0,0 +1,173 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+use crate::{Config, Serializer};
+
+/// A pool of output buffers for serializers, shared between threads.
+///
+/// [`get`](Self::get) returns a serializer writing into a buffer from the pool, which goes back
+/// to the pool when the guard is dropped. This avoids allocating a new buffer for each key. The
+/// lock is only held to take or return a buffer.
+///
+/// # Example
+/// ```
+/// use memcomparable::SerializerPool;
+/// use serde::Serialize;
+///
+/// let pool = SerializerPool::new();
+/// std::thread::scope(|s| {
+///     for i in 0..4u32 {
+///         let pool = &pool;
+///         s.spawn(move || {
+///             let mut ser = pool.get();
+///             (i, "key").serialize(&mut *ser).unwrap();
+///             assert_eq!(ser.bytes(), memcomparable::to_vec(&(i, "key")).unwrap());
+///         });
+///     }
+/// });
+/// ```
+#[derive(Debug, Default)]
+pub struct SerializerPool {
+    config: Config,
+    buffers: Mutex<Vec<Vec<u8>>>,
+}
+
+impl SerializerPool {
+    /// Create an empty pool of serializers with the default [`Config`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty pool of serializers with the given [`Config`].
+    pub fn with_config(config: Config) -> Self {
+        SerializerPool {
+            config,
+            buffers: Mutex::default(),
+        }
+    }
+
+    /// Take a serializer with an empty buffer from the pool, allocating one if there is none.
+    pub fn get(&self) -> PooledSerializer<'_> {
+        let buffer = self.lock().pop().unwrap_or_default();
+        PooledSerializer {
+            pool: self,
+            serializer: Some(Serializer::with_config(buffer, self.config)),
+        }
+    }
+
+    /// Return the number of idle buffers in the pool.
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Vec<u8>>> {
+        // a panic while holding the lock can not leave the stack inconsistent
+        self.buffers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A serializer taken from a [`SerializerPool`], whose buffer is returned on drop.
+pub struct PooledSerializer<'a> {
+    pool: &'a SerializerPool,
+    // only `None` while dropping
+    serializer: Option<Serializer<Vec<u8>>>,
+}
+
+impl PooledSerializer<'_> {
+    /// Return the bytes serialized so far.
+    pub fn bytes(&self) -> &[u8] {
+        self.get_ref()
+    }
+
+    /// Take the serialized bytes, leaving the buffer to be replaced by a new one.
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        self.serializer.take().unwrap().into_inner()
+    }
+}
+
+impl Deref for PooledSerializer<'_> {
+    type Target = Serializer<Vec<u8>>;
+
+    fn deref(&self) -> &Self::Target {
+        self.serializer.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledSerializer<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.serializer.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledSerializer<'_> {
+    fn drop(&mut self) {
+        if let Some(serializer) = self.serializer.take() {
+            let mut buffer = serializer.into_inner();
+            buffer.clear();
+            self.pool.lock().push(buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+
+    #[test]
+    fn test_pool() {
+        let pool = SerializerPool::new();
+        {
+            let mut ser = pool.get();
+            "a long enough key".serialize(&mut *ser).unwrap();
+            assert_eq!(pool.idle(), 0);
+        }
+        assert_eq!(pool.idle(), 1);
+        // the buffer is reused, cleared but with its capacity
+        let ser = pool.get();
+        assert!(ser.bytes().is_empty());
+        assert!(ser.get_ref().capacity() >= 18);
+        drop(ser);
+
+        std::thread::scope(|s| {
+            for t in 0..8u32 {
+                let pool = &pool;
+                s.spawn(move || {
+                    for i in 0..100u32 {
+                        let mut ser = pool.get();
+                        (t, i, "value").serialize(&mut *ser).unwrap();
+                        assert_eq!(ser.bytes(), crate::to_vec(&(t, i, "value")).unwrap());
+                    }
+                });
+            }
+        });
+        assert!((1..=8).contains(&pool.idle()));
+
+        // taken bytes are not returned
+        let idle = pool.idle();
+        let mut ser = pool.get();
+        1u8.serialize(&mut *ser).unwrap();
+        assert_eq!(ser.into_bytes(), [1]);
+        assert_eq!(pool.idle(), idle - 1);
+
+        let pool = SerializerPool::with_config(Config::new().reverse(true));
+        let mut ser = pool.get();
+        1u8.serialize(&mut *ser).unwrap();
+        assert_eq!(ser.bytes(), [0xfe]);
+    }
+}
@@ -12,38 +12,176 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use bytes::BufMut;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+
+use bytes::{BufMut, Bytes, BytesMut};
 use serde::{ser, Serialize};
 
+use crate::bytes_format::{CHUNK_SIZE, CHUNK_UNIT_SIZE};
+use crate::config::{Config, SeqFraming};
+use crate::crc::crc32;
 #[cfg(feature = "decimal")]
-use crate::decimal::Decimal;
+use crate::decimal::{
+    Decimal, FINITE_DECIMAL_NEG_BASE, FINITE_DECIMAL_POS_BASE, FINITE_DECIMAL_ZERO,
+};
+use crate::dynamic::DynValue;
 use crate::error::{Error, Result};
+use crate::float::{f32_key, f64_key, NanOrder};
 
 /// A structure for serializing Rust values into a memcomparable bytes.
+#[derive(Clone)]
 pub struct Serializer<B: BufMut> {
     output: MaybeFlip<B>,
+    human_readable: bool,
+    nan_order: NanOrder,
+    seq_framing: SeqFraming,
+    named_variants: bool,
+    // number of sequences started but not ended yet
+    open_seqs: usize,
+    // prefixed length and number of elements serialized so far of each open length-prefixed
+    // sequence, innermost last
+    seq_lens: Vec<(usize, usize)>,
 }
 
 impl<B: BufMut> Serializer<B> {
     /// Create a new `Serializer`.
+    ///
+    /// Any `BufMut` can be the output, e.g. a `Vec<u8>` or a `BytesMut`.
+    ///
+    /// # Example
+    /// ```
+    /// use bytes::BytesMut;
+    /// use serde::Serialize;
+    ///
+    /// let mut ser = memcomparable::Serializer::new(BytesMut::with_capacity(16));
+    /// (1u8, "key").serialize(&mut ser).unwrap();
+    /// let bytes = ser.into_inner().freeze();
+    /// assert_eq!(bytes, memcomparable::to_vec(&(1u8, "key")).unwrap());
+    /// ```
     pub fn new(buffer: B) -> Self {
         Serializer {
             output: MaybeFlip {
                 output: buffer,
                 flip: false,
             },
+            human_readable: false,
+            nan_order: NanOrder::High,
+            seq_framing: SeqFraming::Marker,
+            named_variants: false,
+            open_seqs: 0,
+            seq_lens: vec![],
         }
     }
 
+    /// Create a new `Serializer` with the given [`Config`].
+    pub fn with_config(buffer: B, config: Config) -> Self {
+        let mut serializer = Self::new(buffer);
+        serializer.set_reverse(config.reverse);
+        serializer.set_human_readable(config.human_readable);
+        serializer.set_nan_order(config.nan_order);
+        serializer.set_seq_framing(config.seq_framing);
+        serializer.set_named_variants(config.named_variants);
+        serializer
+    }
+
     /// Unwrap the inner buffer from the `Serializer`.
     pub fn into_inner(self) -> B {
         self.output.output
     }
 
+    /// Return a reference to the inner buffer.
+    pub fn get_ref(&self) -> &B {
+        &self.output.output
+    }
+
+    /// Unwrap the inner buffer, checking that the output is complete.
+    ///
+    /// Unlike [`into_inner`](Self::into_inner), this returns [`Error::IncompleteSequence`] if a
+    /// sequence was started but never ended, which would leave it without its terminator.
+    pub fn finish(self) -> Result<B> {
+        if self.open_seqs != 0 {
+            return Err(Error::IncompleteSequence);
+        }
+        Ok(self.output.output)
+    }
+
     /// Set whether data is serialized in reverse order.
     pub fn set_reverse(&mut self, reverse: bool) {
         self.output.flip = reverse;
     }
+
+    /// Set the value returned by `is_human_readable`. Default is `false`.
+    ///
+    /// Types like `std::net::IpAddr` serialize differently depending on this flag, so enabling
+    /// it changes the encoding of such types. The `Deserializer` must use the same setting.
+    pub fn set_human_readable(&mut self, human_readable: bool) {
+        self.human_readable = human_readable;
+    }
+
+    /// Set where NaN sorts relative to other floats. Default is [`NanOrder::High`].
+    ///
+    /// With [`NanOrder::Low`], NaN is encoded with all bits inverted, so it sorts before `-inf`.
+    pub fn set_nan_order(&mut self, nan_order: NanOrder) {
+        self.nan_order = nan_order;
+    }
+
+    /// Set how sequences are framed. Default is [`SeqFraming::Marker`].
+    ///
+    /// The `Deserializer` must use the same setting.
+    pub fn set_seq_framing(&mut self, seq_framing: SeqFraming) {
+        self.seq_framing = seq_framing;
+    }
+
+    /// Set whether enum variants are encoded by name instead of index. Default is `false`.
+    ///
+    /// The name is encoded as a string, so stored keys stay valid when variants are reordered
+    /// or inserted, but take more space. **Variants then sort alphabetically by name**, not in
+    /// declaration order. The `Deserializer` must use the same setting.
+    pub fn set_named_variants(&mut self, named_variants: bool) {
+        self.named_variants = named_variants;
+    }
+
+    /// Serialize the tag of an enum variant.
+    fn serialize_variant(&mut self, variant_index: u32, variant: &'static str) -> Result<()> {
+        if self.named_variants {
+            ser::Serializer::serialize_str(self, variant)
+        } else {
+            assert!(variant_index <= u8::MAX as u32, "too many variants");
+            ser::Serializer::serialize_u8(self, variant_index as u8)
+        }
+    }
+}
+
+impl Serializer<Vec<u8>> {
+    /// Unwrap the output like [`finish`](Self::finish) and append a CRC-32 of it as a big-endian
+    /// `u32`.
+    ///
+    /// The checksum detects corruption of stored keys, and is verified by
+    /// [`from_slice_checked`](crate::from_slice_checked). **Checksummed keys are not directly
+    /// comparable**: keys whose bodies are equal up to the end of the shorter one compare by
+    /// the checksum of the shorter one instead.
+    ///
+    /// # Example
+    /// ```
+    /// use serde::Serialize;
+    ///
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// (1u8, "key").serialize(&mut ser).unwrap();
+    /// let bytes = ser.finish_with_checksum().unwrap();
+    /// assert_eq!(
+    ///     memcomparable::from_slice_checked::<(u8, String)>(&bytes).unwrap(),
+    ///     (1, "key".to_string())
+    /// );
+    /// ```
+    pub fn finish_with_checksum(self) -> Result<Vec<u8>> {
+        let mut output = self.finish()?;
+        let checksum = crc32(&output);
+        output.extend_from_slice(&checksum.to_be_bytes());
+        Ok(output)
+    }
 }
 
 /// Serialize the given data structure as a memcomparable byte vector.
@@ -53,7 +191,239 @@ pub fn to_vec(value: &impl Serialize) -> Result<Vec<u8>> {
     Ok(serializer.into_inner())
 }
 
+/// Serialize the given data structure as a memcomparable `Bytes`.
+///
+/// The value is serialized into a `BytesMut` which is then frozen, without copying.
+pub fn to_bytes(value: &impl Serialize) -> Result<Bytes> {
+    let mut serializer = Serializer::new(BytesMut::new());
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner().freeze())
+}
+
+/// Serialize the given data structure into a fixed buffer, returning the encoded length.
+///
+/// This needs no allocation for types of a bounded length, e.g. to encode a key on the stack.
+/// Returns [`Error::BufferTooSmall`] with the full length if the encoding does not fit, in which
+/// case the content of `buf` is unspecified.
+///
+/// # Example
+/// ```
+/// let mut buf = [0; 12];
+/// let len = memcomparable::to_slice(&(1u32, -1i64), &mut buf).unwrap();
+/// assert_eq!(len, 12);
+/// assert_eq!(buf[..len], memcomparable::to_vec(&(1u32, -1i64)).unwrap());
+/// ```
+pub fn to_slice(value: &impl Serialize, buf: &mut [u8]) -> Result<usize> {
+    let mut serializer = Serializer::new(SliceBuf {
+        buf,
+        len: 0,
+        scratch: [0; DISCARD_SCRATCH_SIZE],
+    });
+    value.serialize(&mut serializer)?;
+    let SliceBuf { buf, len, .. } = serializer.into_inner();
+    if len > buf.len() {
+        return Err(Error::BufferTooSmall { needed: len });
+    }
+    Ok(len)
+}
+
+/// Compare two values by their memcomparable encodings.
+///
+/// The values are serialized into buffers kept per thread, so this does not allocate once the
+/// buffers have grown to fit. This is useful as a comparator on a hot path.
+///
+/// # Example
+/// ```
+/// use std::cmp::Ordering;
+///
+/// let ord = memcomparable::compare_by_encoding(&(1u8, "b"), &(1u8, "a")).unwrap();
+/// assert_eq!(ord, Ordering::Greater);
+/// ```
+pub fn compare_by_encoding<T>(a: &T, b: &T) -> Result<Ordering>
+where
+    T: ?Sized + Serialize,
+{
+    thread_local! {
+        static SCRATCH: RefCell<(Vec<u8>, Vec<u8>)> = const { RefCell::new((vec![], vec![])) };
+    }
+
+    fn compare<T: ?Sized + Serialize>(
+        a: &T,
+        b: &T,
+        ea: &mut Vec<u8>,
+        eb: &mut Vec<u8>,
+    ) -> Result<Ordering> {
+        ea.clear();
+        eb.clear();
+        a.serialize(&mut Serializer::new(&mut *ea))?;
+        b.serialize(&mut Serializer::new(&mut *eb))?;
+        Ok(ea.as_slice().cmp(eb))
+    }
+
+    SCRATCH.with(|scratch| match scratch.try_borrow_mut() {
+        Ok(mut scratch) => {
+            let (ea, eb) = &mut *scratch;
+            compare(a, b, ea, eb)
+        }
+        // called again from within a `Serialize` impl
+        Err(_) => compare(a, b, &mut vec![], &mut vec![]),
+    })
+}
+
+/// Serialize the given data structure as a memcomparable byte vector in descending order.
+///
+/// The result sorts in the reverse order of `value`. Use [`from_slice_desc`] to decode it.
+///
+/// [`from_slice_desc`]: crate::from_slice_desc
+///
+/// # Example
+/// ```
+/// let a = memcomparable::to_vec_desc(&1u32).unwrap();
+/// let b = memcomparable::to_vec_desc(&2u32).unwrap();
+/// assert!(a > b);
+/// ```
+pub fn to_vec_desc(value: &impl Serialize) -> Result<Vec<u8>> {
+    let mut serializer = Serializer::new(vec![]);
+    serializer.set_reverse(true);
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner())
+}
+
+/// Serialize a slice of values into one contiguous byte vector.
+///
+/// Returns the bytes and the offsets of each encoded value. The offsets have one more element
+/// than `values`, so the encoding of `values[i]` is `bytes[offsets[i]..offsets[i + 1]]`.
+///
+/// # Example
+/// ```
+/// let (bytes, offsets) = memcomparable::to_vec_batch(&[1u16, 2, 3]).unwrap();
+/// assert_eq!(bytes, [0, 1, 0, 2, 0, 3]);
+/// assert_eq!(offsets, [0, 2, 4, 6]);
+/// ```
+pub fn to_vec_batch<T: Serialize>(values: &[T]) -> Result<(Vec<u8>, Vec<usize>)> {
+    let mut serializer = Serializer::new(vec![]);
+    let mut offsets = Vec::with_capacity(values.len() + 1);
+    offsets.push(0);
+    for value in values {
+        value.serialize(&mut serializer)?;
+        offsets.push(serializer.output.output.len());
+    }
+    Ok((serializer.into_inner(), offsets))
+}
+
+/// Check whether the given data structure can be serialized, without producing any bytes.
+///
+/// Returns the first error serialization would hit, e.g. `Error::NotSupported` for a value
+/// containing a map. This is useful for validating a schema up front.
+///
+/// # Example
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// assert!(memcomparable::can_serialize(&(1u8, "a")).is_ok());
+/// assert!(memcomparable::can_serialize(&BTreeMap::from([(1u8, 2u8)])).is_err());
+/// ```
+pub fn can_serialize(value: &impl Serialize) -> Result<()> {
+    value.serialize(&mut Serializer::new(Discard {
+        scratch: [0; DISCARD_SCRATCH_SIZE],
+    }))
+}
+
+/// Serialize the given data structure into an async writer.
+///
+/// Serde serializers are synchronous, so the value is first serialized into a temporary buffer
+/// which is then written with `write_all`. This is not zero-copy.
+///
+/// # Example
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut writer = vec![];
+/// memcomparable::to_async_writer(&mut writer, &"hello").await.unwrap();
+/// assert_eq!(writer, memcomparable::to_vec(&"hello").unwrap());
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub async fn to_async_writer<W>(writer: &mut W, value: &impl Serialize) -> Result<()>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::AsyncWriteExt;
+
+    let bytes = to_vec(value)?;
+    writer.write_all(&bytes).await?;
+    Ok(())
+}
+
+/// Size of the buffer handed out by `Discard::chunk_mut` and `SliceBuf::chunk_mut` when full.
+const DISCARD_SCRATCH_SIZE: usize = 64;
+
+/// A `BufMut` that discards everything written to it.
+struct Discard {
+    // bytes written through `chunk_mut` land here and are never read
+    scratch: [u8; DISCARD_SCRATCH_SIZE],
+}
+
+unsafe impl BufMut for Discard {
+    fn remaining_mut(&self) -> usize {
+        usize::MAX
+    }
+
+    unsafe fn advance_mut(&mut self, _cnt: usize) {}
+
+    fn chunk_mut(&mut self) -> &mut bytes::buf::UninitSlice {
+        bytes::buf::UninitSlice::new(&mut self.scratch)
+    }
+
+    fn put_slice(&mut self, _src: &[u8]) {}
+
+    fn put_bytes(&mut self, _val: u8, _cnt: usize) {}
+}
+
+/// A `BufMut` over a fixed slice that counts the bytes which do not fit instead of panicking.
+struct SliceBuf<'a> {
+    buf: &'a mut [u8],
+    // the number of bytes written, which may exceed the length of `buf`
+    len: usize,
+    // bytes written through `chunk_mut` past the end of `buf` land here and are never read
+    scratch: [u8; DISCARD_SCRATCH_SIZE],
+}
+
+unsafe impl BufMut for SliceBuf<'_> {
+    fn remaining_mut(&self) -> usize {
+        usize::MAX - self.len
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        self.len += cnt;
+    }
+
+    fn chunk_mut(&mut self) -> &mut bytes::buf::UninitSlice {
+        match self.buf.get_mut(self.len..) {
+            Some(rest) if !rest.is_empty() => bytes::buf::UninitSlice::new(rest),
+            // past the end, bytes are discarded as they would not fit anyway
+            _ => bytes::buf::UninitSlice::new(&mut self.scratch),
+        }
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        if let Some(dst) = self.buf.get_mut(self.len..self.len + src.len()) {
+            dst.copy_from_slice(src);
+        }
+        self.len += src.len();
+    }
+
+    fn put_bytes(&mut self, val: u8, cnt: usize) {
+        if let Some(dst) = self.buf.get_mut(self.len..self.len + cnt) {
+            dst.fill(val);
+        }
+        self.len += cnt;
+    }
+}
+
 /// A wrapper around `BufMut` that can flip bits when putting data.
+#[derive(Clone)]
 struct MaybeFlip<B: BufMut> {
     output: B,
     flip: bool,
@@ -79,22 +449,25 @@ impl<B: BufMut> MaybeFlip<B> {
     def_method!(put_u128, u128);
 
     fn put_slice(&mut self, src: &[u8]) {
+        if !self.flip {
+            self.output.put_slice(src);
+            return;
+        }
         for &val in src {
-            let val = if self.flip { !val } else { val };
-            self.output.put_u8(val);
+            self.output.put_u8(!val);
         }
     }
 
     fn put_bytes(&mut self, val: u8, cnt: usize) {
-        let val = if self.flip { !val } else { val };
-        self.output.put_bytes(val, cnt);
+        self.output
+            .put_bytes(if self.flip { !val } else { val }, cnt);
     }
 }
 
 // Format Reference:
 // https://github.com/facebook/mysql-5.6/wiki/MyRocks-record-format#memcomparable-format
 // https://haxisnake.github.io/2020/11/06/TIDB源码学习笔记-基本类型编解码方案/
-impl<'a, B: BufMut> ser::Serializer for &'a mut Serializer<B> {
+impl<B: BufMut> ser::Serializer for &mut Serializer<B> {
     type Error = Error;
     type Ok = ();
     type SerializeMap = Self;
@@ -110,27 +483,27 @@ impl<'a, B: BufMut> ser::Serializer for &'a mut Serializer<B> {
     }
 
     fn serialize_i8(self, v: i8) -> Result<()> {
-        let u = v as u8 ^ (1 << 7);
+        let u = v as u8 ^ (1u8 << 7);
         self.serialize_u8(u)
     }
 
     fn serialize_i16(self, v: i16) -> Result<()> {
-        let u = v as u16 ^ (1 << 15);
+        let u = v as u16 ^ (1u16 << 15);
         self.serialize_u16(u)
     }
 
     fn serialize_i32(self, v: i32) -> Result<()> {
-        let u = v as u32 ^ (1 << 31);
+        let u = v as u32 ^ (1u32 << 31);
         self.serialize_u32(u)
     }
 
     fn serialize_i64(self, v: i64) -> Result<()> {
-        let u = v as u64 ^ (1 << 63);
+        let u = v as u64 ^ (1u64 << 63);
         self.serialize_u64(u)
     }
 
     fn serialize_i128(self, v: i128) -> Result<()> {
-        let u = v as u128 ^ (1 << 127);
+        let u = v as u128 ^ (1u128 << 127);
         self.serialize_u128(u)
     }
 
@@ -159,35 +532,23 @@ impl<'a, B: BufMut> ser::Serializer for &'a mut Serializer<B> {
         Ok(())
     }
 
-    fn serialize_f32(self, mut v: f32) -> Result<()> {
-        if v.is_nan() {
-            v = f32::NAN; // normalize pos/neg NaN
-        } else if v == 0.0 {
-            v = 0.0; // normalize pos/neg zero
-        }
-        let u = v.to_bits();
-        let u = if v.is_sign_positive() {
-            u | (1 << 31)
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        let key = f32_key(v);
+        if v.is_nan() && self.nan_order == NanOrder::Low {
+            self.output.put_u32(!key);
         } else {
-            !u
-        };
-        self.output.put_u32(u);
+            self.output.put_u32(key);
+        }
         Ok(())
     }
 
-    fn serialize_f64(self, mut v: f64) -> Result<()> {
-        if v.is_nan() {
-            v = f64::NAN; // normalize pos/neg NaN
-        } else if v == 0.0 {
-            v = 0.0; // normalize pos/neg zero
-        }
-        let u = v.to_bits();
-        let u = if v.is_sign_positive() {
-            u | (1 << 63)
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        let key = f64_key(v);
+        if v.is_nan() && self.nan_order == NanOrder::Low {
+            self.output.put_u64(!key);
         } else {
-            !u
-        };
-        self.output.put_u64(u);
+            self.output.put_u64(key);
+        }
         Ok(())
     }
 
@@ -201,18 +562,8 @@ impl<'a, B: BufMut> ser::Serializer for &'a mut Serializer<B> {
 
     fn serialize_bytes(self, v: &[u8]) -> Result<()> {
         self.output.put_u8(!v.is_empty() as u8);
-        let mut len = 0;
-        for chunk in v.chunks(8) {
-            self.output.put_slice(chunk);
-            if chunk.len() != 8 {
-                self.output.put_bytes(0, 8 - chunk.len());
-            }
-            len += chunk.len();
-            // append an extra byte that signals the number of significant bytes in this chunk
-            // 1-8: many bytes were significant and this group is the last group
-            // 9: all 8 bytes were significant and there is more data to come
-            let extra = if len == v.len() { chunk.len() as u8 } else { 9 };
-            self.output.put_u8(extra);
+        if !v.is_empty() {
+            self.put_chunks(v);
         }
         Ok(())
     }
@@ -241,10 +592,9 @@ impl<'a, B: BufMut> ser::Serializer for &'a mut Serializer<B> {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<()> {
-        assert!(variant_index <= u8::MAX as u32, "too many variants");
-        self.serialize_u8(variant_index as u8)
+        self.serialize_variant(variant_index, variant)
     }
 
     fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
@@ -258,19 +608,26 @@ impl<'a, B: BufMut> ser::Serializer for &'a mut Serializer<B> {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         value: &T,
     ) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        assert!(variant_index <= u8::MAX as u32, "too many variants");
-        self.serialize_u8(variant_index as u8)?;
+        self.serialize_variant(variant_index, variant)?;
         value.serialize(&mut *self)?;
         Ok(())
     }
 
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        if self.seq_framing == SeqFraming::LengthPrefixed {
+            let len = len.ok_or(Error::NotSupported("sequence of unknown length"))?;
+            let prefix = u32::try_from(len)
+                .map_err(|_| <Error as ser::Error>::custom("sequence is too long"))?;
+            self.output.put_u32(prefix);
+            self.seq_lens.push((len, 0));
+        }
+        self.open_seqs += 1;
         Ok(self)
     }
 
@@ -290,11 +647,10 @@ impl<'a, B: BufMut> ser::Serializer for &'a mut Serializer<B> {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        assert!(variant_index <= u8::MAX as u32, "too many variants");
-        self.serialize_u8(variant_index as u8)?;
+        self.serialize_variant(variant_index, variant)?;
         Ok(self)
     }
 
@@ -310,20 +666,19 @@ impl<'a, B: BufMut> ser::Serializer for &'a mut Serializer<B> {
         self,
         _name: &'static str,
         variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        assert!(variant_index <= u8::MAX as u32, "too many variants");
-        self.serialize_u8(variant_index as u8)?;
+        self.serialize_variant(variant_index, variant)?;
         Ok(self)
     }
 
     fn is_human_readable(&self) -> bool {
-        false
+        self.human_readable
     }
 }
 
-impl<'a, B: BufMut> ser::SerializeSeq for &'a mut Serializer<B> {
+impl<B: BufMut> ser::SerializeSeq for &mut Serializer<B> {
     type Error = Error;
     type Ok = ();
 
@@ -332,18 +687,37 @@ impl<'a, B: BufMut> ser::SerializeSeq for &'a mut Serializer<B> {
         T: ?Sized + Serialize,
     {
         use serde::Serializer;
-        self.serialize_u8(1)?;
+        // Each element is preceded by a `1` and the sequence is terminated by a `0`. The marker
+        // is read by the sequence before the element is decoded, so an element with its own tag
+        // is framed in a separate layer, e.g. `[None::<u8>]` is `1 0 0`.
+        match self.seq_framing {
+            SeqFraming::Marker => self.serialize_u8(1)?,
+            SeqFraming::LengthPrefixed => {
+                if let Some((_, count)) = self.seq_lens.last_mut() {
+                    *count += 1;
+                }
+            }
+        }
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
         use serde::Serializer;
-        self.serialize_u8(0)?;
-        Ok(())
+        self.open_seqs -= 1;
+        match self.seq_framing {
+            SeqFraming::Marker => self.serialize_u8(0),
+            SeqFraming::LengthPrefixed => match self.seq_lens.pop() {
+                // the prefix is taken from the length hint, which the elements must match
+                Some((expected, actual)) if expected != actual => {
+                    Err(Error::SeqLengthMismatch { expected, actual })
+                }
+                _ => Ok(()),
+            },
+        }
     }
 }
 
-impl<'a, B: BufMut> ser::SerializeTuple for &'a mut Serializer<B> {
+impl<B: BufMut> ser::SerializeTuple for &mut Serializer<B> {
     type Error = Error;
     type Ok = ();
 
@@ -359,7 +733,7 @@ impl<'a, B: BufMut> ser::SerializeTuple for &'a mut Serializer<B> {
     }
 }
 
-impl<'a, B: BufMut> ser::SerializeTupleStruct for &'a mut Serializer<B> {
+impl<B: BufMut> ser::SerializeTupleStruct for &mut Serializer<B> {
     type Error = Error;
     type Ok = ();
 
@@ -375,7 +749,7 @@ impl<'a, B: BufMut> ser::SerializeTupleStruct for &'a mut Serializer<B> {
     }
 }
 
-impl<'a, B: BufMut> ser::SerializeTupleVariant for &'a mut Serializer<B> {
+impl<B: BufMut> ser::SerializeTupleVariant for &mut Serializer<B> {
     type Error = Error;
     type Ok = ();
 
@@ -391,7 +765,7 @@ impl<'a, B: BufMut> ser::SerializeTupleVariant for &'a mut Serializer<B> {
     }
 }
 
-impl<'a, B: BufMut> ser::SerializeMap for &'a mut Serializer<B> {
+impl<B: BufMut> ser::SerializeMap for &mut Serializer<B> {
     type Error = Error;
     type Ok = ();
 
@@ -414,7 +788,7 @@ impl<'a, B: BufMut> ser::SerializeMap for &'a mut Serializer<B> {
     }
 }
 
-impl<'a, B: BufMut> ser::SerializeStruct for &'a mut Serializer<B> {
+impl<B: BufMut> ser::SerializeStruct for &mut Serializer<B> {
     type Error = Error;
     type Ok = ();
 
@@ -430,7 +804,7 @@ impl<'a, B: BufMut> ser::SerializeStruct for &'a mut Serializer<B> {
     }
 }
 
-impl<'a, B: BufMut> ser::SerializeStructVariant for &'a mut Serializer<B> {
+impl<B: BufMut> ser::SerializeStructVariant for &mut Serializer<B> {
     type Error = Error;
     type Ok = ();
 
@@ -447,88 +821,645 @@ impl<'a, B: BufMut> ser::SerializeStructVariant for &'a mut Serializer<B> {
 }
 
 impl<B: BufMut> Serializer<B> {
-    /// Serialize a decimal value.
+    /// Write a non-empty byte array as chunks, without the leading marker.
+    fn put_chunks(&mut self, v: &[u8]) {
+        // each chunk is followed by an extra byte that signals the number of significant bytes
+        // 1-8: many bytes were significant and this group is the last group
+        // 9: all 8 bytes were significant and there is more data to come
+        let (body, last) = v.split_at((v.len() - 1) / CHUNK_SIZE * CHUNK_SIZE);
+        let mut group = [0u8; CHUNK_UNIT_SIZE];
+        for chunk in body.chunks_exact(CHUNK_SIZE) {
+            group[..CHUNK_SIZE].copy_from_slice(chunk);
+            group[CHUNK_SIZE] = 9;
+            self.output.put_slice(&group);
+        }
+        let mut group = [0u8; CHUNK_UNIT_SIZE];
+        group[..last.len()].copy_from_slice(last);
+        group[CHUNK_SIZE] = last.len() as u8;
+        self.output.put_slice(&group);
+    }
+
+    /// Append a pre-encoded memcomparable segment to the output.
     ///
-    /// The encoding format follows `SQLite`: <https://sqlite.org/src4/doc/trunk/www/key_encoding.wiki>
-    /// except that NaN is considered larger than +Infinity, to be consistent with f32 and f64.
+    /// The bytes are flipped if the serializer is in reverse order, so `bytes` should be an
+    /// ascending encoding. The caller is responsible for its validity.
+    pub fn put_encoded(&mut self, bytes: &[u8]) {
+        self.output.put_slice(bytes);
+    }
+
+    /// Serialize a byte array whose length is known out of band, such as `[u8; 16]`.
+    ///
+    /// The bytes are written as is, without chunk markers or length, and must be read back by
+    /// [`Deserializer::deserialize_fixed_bytes`] with the same length. Arrays of the same length
+    /// keep their lexicographic order.
+    ///
+    /// [`Deserializer::deserialize_fixed_bytes`]: crate::Deserializer::deserialize_fixed_bytes
     ///
     /// # Example
     /// ```
-    /// use memcomparable::Decimal;
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_fixed_bytes(&[0x12, 0x34]).unwrap();
+    /// assert_eq!(ser.into_inner(), [0x12, 0x34]);
+    /// ```
+    pub fn serialize_fixed_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.output.put_slice(bytes);
+        Ok(())
+    }
+
+    /// Serialize a string padded with zeros to exactly `width` bytes.
     ///
-    /// let d1 = Decimal::Normalized("12.34".parse().unwrap());
-    /// let d2 = Decimal::Inf;
+    /// No chunk markers or length are written, so every key has the same width. It must be read
+    /// back by [`Deserializer::deserialize_fixed_str`] with the same width, and only strings of
+    /// the same width keep their order.
+    ///
+    /// Returns [`Error::LengthLimitExceeded`] if the string is longer than `width`, rather than
+    /// truncating it. Since the padding is trimmed on decoding, strings that end with `'\0'`
+    /// collide with the same strings without it.
+    ///
+    /// [`Deserializer::deserialize_fixed_str`]: crate::Deserializer::deserialize_fixed_str
     ///
+    /// # Example
+    /// ```
     /// let mut ser = memcomparable::Serializer::new(vec![]);
-    /// ser.serialize_decimal(d1.into()).unwrap();
-    /// ser.serialize_decimal(d2).unwrap();
+    /// ser.serialize_fixed_str("ab", 4).unwrap();
+    /// assert_eq!(ser.into_inner(), [b'a', b'b', 0, 0]);
     /// ```
-    #[cfg(feature = "decimal")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
-    pub fn serialize_decimal(&mut self, decimal: Decimal) -> Result<()> {
-        let decimal = match decimal {
-            Decimal::NaN => {
-                self.output.put_u8(0x24);
-                return Ok(());
-            }
-            Decimal::NegInf => {
-                self.output.put_u8(0x07);
-                return Ok(());
-            }
-            Decimal::Inf => {
-                self.output.put_u8(0x23);
-                return Ok(());
-            }
-            Decimal::Normalized(d) if d.is_zero() => {
-                self.output.put_u8(0x15);
-                return Ok(());
-            }
-            Decimal::Normalized(d) => d,
-        };
-        let (exponent, significand) = Self::decimal_e_m(decimal);
-        if decimal.is_sign_positive() {
-            match exponent {
-                11.. => {
-                    self.output.put_u8(0x22);
-                    self.output.put_u8(exponent as u8);
-                }
-                0..=10 => {
-                    self.output.put_u8(0x17 + exponent as u8);
-                }
-                _ => {
-                    self.output.put_u8(0x16);
-                    self.output.put_u8(!(-exponent) as u8);
-                }
+    pub fn serialize_fixed_str(&mut self, s: &str, width: usize) -> Result<()> {
+        if s.len() > width {
+            return Err(Error::LengthLimitExceeded { limit: width });
+        }
+        self.output.put_slice(s.as_bytes());
+        self.output.put_bytes(0, width - s.len());
+        Ok(())
+    }
+
+    /// Serialize a slice of bools packed as bits, 8 per byte.
+    ///
+    /// The bits are written most significant first, and the low bits of the last byte are
+    /// zero. Like [`serialize_fixed_bytes`](Self::serialize_fixed_bytes), no length is written:
+    /// the slice must be read back by [`Deserializer::deserialize_bool_slice`] with the same
+    /// length, and only slices of the same length keep their order.
+    ///
+    /// [`Deserializer::deserialize_bool_slice`]: crate::Deserializer::deserialize_bool_slice
+    ///
+    /// # Example
+    /// ```
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_bool_slice(&[true, false, true, true, false, false, false, false, true])
+    ///     .unwrap();
+    /// assert_eq!(ser.into_inner(), [0b1011_0000, 0b1000_0000]);
+    /// ```
+    pub fn serialize_bool_slice(&mut self, bits: &[bool]) -> Result<()> {
+        for chunk in bits.chunks(8) {
+            let byte = chunk
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (i, &bit)| byte | (bit as u8) << (7 - i));
+            self.output.put_u8(byte);
+        }
+        Ok(())
+    }
+
+    /// Serialize an `f32`, returning [`Error::NanNotAllowed`] if it is NaN.
+    ///
+    /// Other values are encoded as by `serialize_f32`. Use this for columns that must not
+    /// contain NaN, instead of storing a key that sorts above every number.
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::{Error, Serializer};
+    ///
+    /// let mut ser = Serializer::new(vec![]);
+    /// ser.serialize_f32_checked(1.0).unwrap();
+    /// assert_eq!(ser.serialize_f32_checked(f32::NAN), Err(Error::NanNotAllowed));
+    /// assert_eq!(ser.into_inner(), memcomparable::to_vec(&1.0f32).unwrap());
+    /// ```
+    pub fn serialize_f32_checked(&mut self, v: f32) -> Result<()> {
+        if v.is_nan() {
+            return Err(Error::NanNotAllowed);
+        }
+        ser::Serializer::serialize_f32(self, v)
+    }
+
+    /// Serialize an `f64`, returning [`Error::NanNotAllowed`] if it is NaN.
+    ///
+    /// See [`serialize_f32_checked`](Self::serialize_f32_checked).
+    pub fn serialize_f64_checked(&mut self, v: f64) -> Result<()> {
+        if v.is_nan() {
+            return Err(Error::NanNotAllowed);
+        }
+        ser::Serializer::serialize_f64(self, v)
+    }
+
+    /// Serialize `len` bytes read from `reader` as a byte array.
+    ///
+    /// The output is the same as `serialize_bytes` on the data, but it is streamed 8 bytes at a
+    /// time instead of being held in memory. If the reader fails or ends before `len` bytes, an
+    /// [`Error::Io`] is returned and the output is left with a partial encoding.
+    ///
+    /// # Example
+    /// ```
+    /// let data = b"a long blob";
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_bytes_from_reader(&data[..], data.len()).unwrap();
+    /// assert_eq!(ser.into_inner(), memcomparable::to_vec(&"a long blob").unwrap());
+    /// ```
+    pub fn serialize_bytes_from_reader<R: std::io::Read>(
+        &mut self,
+        mut reader: R,
+        len: usize,
+    ) -> Result<()> {
+        self.output.put_u8((len != 0) as u8);
+        if len == 0 {
+            return Ok(());
+        }
+        // see `serialize_bytes` for the meaning of the extra byte after each chunk
+        let mut group = [0u8; CHUNK_UNIT_SIZE];
+        let mut remaining = len;
+        while remaining > CHUNK_SIZE {
+            reader.read_exact(&mut group[..CHUNK_SIZE])?;
+            group[CHUNK_SIZE] = 9;
+            self.output.put_slice(&group);
+            remaining -= CHUNK_SIZE;
+        }
+        let mut group = [0u8; CHUNK_UNIT_SIZE];
+        reader.read_exact(&mut group[..remaining])?;
+        group[CHUNK_SIZE] = remaining as u8;
+        self.output.put_slice(&group);
+        Ok(())
+    }
+
+    /// Serialize an IP address.
+    ///
+    /// The family is written as `0` for v4 or `1` for v6, followed by the octets, so all v4
+    /// addresses sort before v6 ones. This is the same as serde's non-human-readable encoding of
+    /// `IpAddr`, but does not depend on [`set_human_readable`](Self::set_human_readable).
+    pub fn serialize_ip_addr(&mut self, addr: IpAddr) -> Result<()> {
+        match addr {
+            IpAddr::V4(ip) => {
+                self.output.put_u8(0);
+                self.output.put_slice(&ip.octets());
             }
-            self.output.put_slice(&significand);
-        } else {
-            match exponent {
-                11.. => {
-                    self.output.put_u8(0x8);
-                    self.output.put_u8(!exponent as u8);
-                }
-                0..=10 => {
-                    self.output.put_u8(0x13 - exponent as u8);
-                }
-                _ => {
-                    self.output.put_u8(0x14);
-                    self.output.put_u8(-exponent as u8);
-                }
+            IpAddr::V6(ip) => {
+                self.output.put_u8(1);
+                self.output.put_slice(&ip.octets());
             }
-            for b in significand {
-                self.output.put_u8(!b);
+        }
+        Ok(())
+    }
+
+    /// Serialize a nullable string as a single field.
+    ///
+    /// The marker byte is `0` for `None`, `1` for an empty string, and `2` for a non-empty one
+    /// followed by its chunks, giving the SQL order `NULL < '' < 'a'`. This is one byte shorter
+    /// than an `Option<&str>` for non-null values.
+    ///
+    /// # Example
+    /// ```
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_nullable_str(None).unwrap();
+    /// ser.serialize_nullable_str(Some("")).unwrap();
+    /// assert_eq!(ser.into_inner(), [0, 1]);
+    /// ```
+    pub fn serialize_nullable_str(&mut self, v: Option<&str>) -> Result<()> {
+        match v {
+            None => self.output.put_u8(0),
+            Some("") => self.output.put_u8(1),
+            Some(s) => {
+                self.output.put_u8(2);
+                self.put_chunks(s.as_bytes());
             }
         }
         Ok(())
     }
 
-    /// Get the exponent and significand mantissa from a decimal.
-    #[cfg(feature = "decimal")]
-    fn decimal_e_m(decimal: rust_decimal::Decimal) -> (i8, Vec<u8>) {
-        if decimal.is_zero() {
-            return (0, vec![]);
+    /// Serialize a nullable value with the given order of nulls.
+    ///
+    /// `None` is written as `0` if `nulls_last` is false, or as `0xff` otherwise, and `Some(v)`
+    /// as `1` followed by `v`. This gives SQL `NULLS FIRST` or `NULLS LAST` per column. With
+    /// `nulls_last` false, the encoding is the same as that of an `Option<T>`. Reverse order
+    /// also reverses the order of nulls.
+    ///
+    /// # Example
+    /// ```
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_nullable(None::<&u8>, true).unwrap();
+    /// ser.serialize_nullable(Some(&2u8), true).unwrap();
+    /// assert_eq!(ser.into_inner(), [0xff, 1, 2]);
+    /// ```
+    pub fn serialize_nullable<T>(&mut self, value: Option<&T>, nulls_last: bool) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        match value {
+            None => self.output.put_u8(if nulls_last { 0xff } else { 0 }),
+            Some(v) => {
+                self.output.put_u8(1);
+                v.serialize(&mut *self)?;
+            }
         }
-        const POW10: [u128; 30] = [
+        Ok(())
+    }
+
+    /// Serialize a value preceded by an explicit tag byte.
+    ///
+    /// Unlike a serde enum, whose tag is the variant index and thus changes when variants are
+    /// reordered, the tag is chosen by the caller. Values sort by tag first, then by value.
+    ///
+    /// # Example
+    /// ```
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_tagged(7, &1u16).unwrap();
+    /// assert_eq!(ser.into_inner(), [7, 0, 1]);
+    /// ```
+    pub fn serialize_tagged<T: ?Sized + Serialize>(&mut self, tag: u8, value: &T) -> Result<()> {
+        self.output.put_u8(tag);
+        value.serialize(self)
+    }
+
+    /// Serialize a dynamically typed value, preceded by a tag of its type.
+    ///
+    /// Values of different types sort in the order of the tags, given by [`DynValue::tag`], so a
+    /// column can hold values of several types and still be totally ordered.
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::{DynValue, Serializer};
+    ///
+    /// let serialize = |v: DynValue| {
+    ///     let mut ser = Serializer::new(vec![]);
+    ///     ser.serialize_dynamic(&v).unwrap();
+    ///     ser.into_inner()
+    /// };
+    /// assert!(serialize(DynValue::Int(100)) < serialize(DynValue::Float(-1.0)));
+    /// assert!(serialize(DynValue::Float(1.0)) < serialize(DynValue::String("a".into())));
+    /// ```
+    pub fn serialize_dynamic(&mut self, value: &DynValue) -> Result<()> {
+        self.output.put_u8(value.tag());
+        match value {
+            DynValue::Null => Ok(()),
+            DynValue::Bool(v) => v.serialize(self),
+            DynValue::Int(v) => v.serialize(self),
+            DynValue::Float(v) => v.serialize(self),
+            DynValue::String(v) => v.serialize(self),
+            DynValue::Bytes(v) => ser::Serializer::serialize_bytes(self, v),
+        }
+    }
+
+    /// Serialize a row of nullable columns as a null bitmap followed by the present values.
+    ///
+    /// The bitmap is written as by [`serialize_bool_slice`](Self::serialize_bool_slice), with a
+    /// `1` for each present column, and each present value follows as by
+    /// [`serialize_dynamic`](Self::serialize_dynamic). A null takes only its bit, instead of a
+    /// tag per column with `Option`.
+    ///
+    /// **The order differs from that of a tuple of options**: rows compare by their bitmaps
+    /// first, so rows are grouped by which columns are null before any value is compared. E.g.
+    /// `(2, NULL)` sorts before `(1, 3)`.
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::DynValue;
+    ///
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_row_with_nulls(&[None, Some(DynValue::Bool(true)), None])
+    ///     .unwrap();
+    /// assert_eq!(ser.into_inner(), [0b0100_0000, 1, 1]);
+    /// ```
+    pub fn serialize_row_with_nulls(&mut self, values: &[Option<DynValue>]) -> Result<()> {
+        let bitmap: Vec<bool> = values.iter().map(Option::is_some).collect();
+        self.serialize_bool_slice(&bitmap)?;
+        for value in values.iter().flatten() {
+            self.serialize_dynamic(value)?;
+        }
+        Ok(())
+    }
+
+    /// Serialize a timestamp in milliseconds since the Unix epoch.
+    ///
+    /// The encoding is the same as an `i64`, so timestamps before the epoch sort first. Use
+    /// [`system_time_to_millis`](crate::system_time_to_millis) to convert from a `SystemTime`.
+    ///
+    /// # Example
+    /// ```
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_timestamp_millis(-1).unwrap();
+    /// ser.serialize_timestamp_millis(0).unwrap();
+    /// let bytes = ser.into_inner();
+    /// assert!(bytes[..8] < bytes[8..]);
+    /// ```
+    pub fn serialize_timestamp_millis(&mut self, millis: i64) -> Result<()> {
+        ser::Serializer::serialize_i64(self, millis)
+    }
+
+    /// Serialize a timestamp in microseconds since the Unix epoch.
+    ///
+    /// The encoding is the same as an `i64`, so timestamps before the epoch sort first. Use
+    /// [`system_time_to_micros`](crate::system_time_to_micros) to convert from a `SystemTime`.
+    pub fn serialize_timestamp_micros(&mut self, micros: i64) -> Result<()> {
+        ser::Serializer::serialize_i64(self, micros)
+    }
+
+    /// Serialize a signed `chrono::TimeDelta` as whole seconds and nanoseconds.
+    ///
+    /// The seconds are rounded down and written as an `i64`, followed by the nanoseconds within
+    /// the second as a `u32`, so negative deltas sort before zero and positive ones, and the
+    /// full nanosecond precision is kept.
+    ///
+    /// # Example
+    /// ```
+    /// use chrono::TimeDelta;
+    ///
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_time_delta(TimeDelta::milliseconds(-1500)).unwrap();
+    /// assert_eq!(
+    ///     ser.into_inner(),
+    ///     memcomparable::to_vec(&(-2i64, 500_000_000u32)).unwrap()
+    /// );
+    /// ```
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    pub fn serialize_time_delta(&mut self, delta: chrono::TimeDelta) -> Result<()> {
+        // `subsec_nanos` has the sign of the delta; borrow a second to make it positive
+        let (mut secs, mut nanos) = (delta.num_seconds(), delta.subsec_nanos());
+        if nanos < 0 {
+            secs -= 1;
+            nanos += 1_000_000_000;
+        }
+        ser::Serializer::serialize_i64(&mut *self, secs)?;
+        ser::Serializer::serialize_u32(self, nanos as u32)
+    }
+
+    /// Serialize a socket address as its IP address followed by the port.
+    ///
+    /// Addresses sort by IP and then by port. The `flowinfo` and `scope_id` of v6 addresses are
+    /// not encoded: they do not take part in routing to the endpoint, and leaving them out keeps
+    /// the encoding identical to serde's non-human-readable encoding of `SocketAddr`.
+    ///
+    /// # Example
+    /// ```
+    /// let addr: std::net::SocketAddr = "10.0.0.1:80".parse().unwrap();
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_socket_addr(addr).unwrap();
+    /// assert_eq!(ser.into_inner(), [0, 10, 0, 0, 1, 0, 80]);
+    /// ```
+    pub fn serialize_socket_addr(&mut self, addr: SocketAddr) -> Result<()> {
+        self.serialize_ip_addr(addr.ip())?;
+        self.output.put_u16(addr.port());
+        Ok(())
+    }
+
+    /// Serialize a map as a sequence of key/value pairs sorted by key.
+    ///
+    /// The encoding is identical to a `Vec<(K, V)>` of the sorted entries, so it does not depend
+    /// on the iteration order of the map, and maps compare like their sorted entry lists.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// let map = HashMap::from([(2u8, 'b'), (1u8, 'a')]);
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_sorted_map(&map).unwrap();
+    /// assert_eq!(
+    ///     ser.into_inner(),
+    ///     memcomparable::to_vec(&vec![(1u8, 'a'), (2u8, 'b')]).unwrap()
+    /// );
+    /// ```
+    pub fn serialize_sorted_map<K, V, S>(&mut self, map: &HashMap<K, V, S>) -> Result<()>
+    where
+        K: Ord + Serialize,
+        V: Serialize,
+    {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        entries.serialize(self)
+    }
+
+    /// Serialize key/value pairs that are already sorted by key.
+    ///
+    /// The encoding is identical to a `Vec<(K, V)>` of the entries. This avoids collecting a map
+    /// that is known to be sorted, e.g. a `BTreeMap`, or entries read from a sorted source.
+    ///
+    /// Returns an error if the keys are not strictly ascending.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// let map = BTreeMap::from([(2u8, 'b'), (1u8, 'a')]);
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_entries(map.iter()).unwrap();
+    /// let bytes = ser.into_inner();
+    /// let entries: Vec<(u8, char)> = memcomparable::from_slice(&bytes).unwrap();
+    /// assert_eq!(entries, [(1, 'a'), (2, 'b')]);
+    /// ```
+    pub fn serialize_entries<K, V>(&mut self, entries: impl Iterator<Item = (K, V)>) -> Result<()>
+    where
+        K: Ord + Serialize,
+        V: Serialize,
+    {
+        let len = match entries.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(lower),
+            _ => None,
+        };
+        let mut seq = ser::Serializer::serialize_seq(&mut *self, len)?;
+        let mut last_key = None;
+        for (key, value) in entries {
+            if last_key.as_ref().is_some_and(|last| *last >= key) {
+                // the sequence is abandoned, so `finish` must not report it as incomplete
+                seq.open_seqs -= 1;
+                if seq.seq_framing == SeqFraming::LengthPrefixed {
+                    seq.seq_lens.pop();
+                }
+                return Err(ser::Error::custom("entries are not sorted by key"));
+            }
+            ser::SerializeSeq::serialize_element(&mut seq, &(&key, &value))?;
+            last_key = Some(key);
+        }
+        ser::SerializeSeq::end(seq)
+    }
+
+    /// Serialize a set as a sequence of its elements in ascending order.
+    ///
+    /// The encoding is identical to a `Vec<T>` of the sorted elements, so it does not depend on
+    /// the iteration order of the set. It can be deserialized as a `HashSet<T>` directly. A
+    /// `BTreeSet` already iterates in order and needs no special handling.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::{BTreeSet, HashSet};
+    ///
+    /// let set = HashSet::from(["b", "a"]);
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_sorted_set(&set).unwrap();
+    /// let bytes = ser.into_inner();
+    /// assert_eq!(bytes, memcomparable::to_vec(&BTreeSet::from(["a", "b"])).unwrap());
+    /// assert_eq!(memcomparable::from_slice::<HashSet<String>>(&bytes).unwrap().len(), 2);
+    /// ```
+    pub fn serialize_sorted_set<T, S>(&mut self, set: &HashSet<T, S>) -> Result<()>
+    where
+        T: Ord + Serialize,
+    {
+        let mut elements: Vec<_> = set.iter().collect();
+        elements.sort_unstable();
+        elements.serialize(self)
+    }
+
+    /// Serialize a value prefixed by the length of its encoding.
+    ///
+    /// This is useful for embedding a memcomparable key in a larger record that is not itself
+    /// memcomparable. The length is written as a plain `u32`, so **framed values do not preserve
+    /// the order** of the original values.
+    ///
+    /// # Example
+    /// ```
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_framed(&"hello").unwrap();
+    /// let bytes = ser.into_inner();
+    /// assert_eq!(bytes[..4], [0, 0, 0, 10]);
+    ///
+    /// let mut de = memcomparable::Deserializer::new(bytes.as_slice());
+    /// assert_eq!(de.deserialize_framed::<String>().unwrap(), "hello");
+    /// ```
+    pub fn serialize_framed(&mut self, value: &impl Serialize) -> Result<()> {
+        let mut inner = Serializer::new(vec![]);
+        inner.human_readable = self.human_readable;
+        inner.nan_order = self.nan_order;
+        inner.seq_framing = self.seq_framing;
+        inner.named_variants = self.named_variants;
+        value.serialize(&mut inner)?;
+        let bytes = inner.into_inner();
+        let len = u32::try_from(bytes.len())
+            .map_err(|_| <Error as ser::Error>::custom("framed value is too large"))?;
+        self.output.put_u32(len);
+        self.output.put_slice(&bytes);
+        Ok(())
+    }
+
+    /// Serialize a decimal value.
+    ///
+    /// The encoding format follows `SQLite`: <https://sqlite.org/src4/doc/trunk/www/key_encoding.wiki>
+    /// except that NaN is considered larger than +Infinity, to be consistent with f32 and f64.
+    ///
+    /// # Example
+    /// ```
+    /// use memcomparable::Decimal;
+    ///
+    /// let d1 = Decimal::Normalized("12.34".parse().unwrap());
+    /// let d2 = Decimal::Inf;
+    ///
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_decimal(d1.into()).unwrap();
+    /// ser.serialize_decimal(d2).unwrap();
+    /// ```
+    #[cfg(feature = "decimal")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+    pub fn serialize_decimal(&mut self, decimal: Decimal) -> Result<()> {
+        let decimal = match decimal {
+            Decimal::NaN => {
+                self.output.put_u8(0x24);
+                return Ok(());
+            }
+            Decimal::NegInf => {
+                self.output.put_u8(0x07);
+                return Ok(());
+            }
+            Decimal::Inf => {
+                self.output.put_u8(0x23);
+                return Ok(());
+            }
+            Decimal::Normalized(d) if d.is_zero() => {
+                self.output.put_u8(0x15);
+                return Ok(());
+            }
+            Decimal::Normalized(d) => d,
+        };
+        let (exponent, significand) = Self::decimal_e_m(decimal);
+        if decimal.is_sign_positive() {
+            match exponent {
+                11.. => {
+                    self.output.put_u8(0x22);
+                    self.output.put_u8(exponent as u8);
+                }
+                0..=10 => {
+                    self.output.put_u8(0x17 + exponent as u8);
+                }
+                _ => {
+                    self.output.put_u8(0x16);
+                    self.output.put_u8(!(-exponent) as u8);
+                }
+            }
+        } else {
+            match exponent {
+                11.. => {
+                    self.output.put_u8(0x8);
+                    self.output.put_u8(!exponent as u8);
+                }
+                0..=10 => {
+                    self.output.put_u8(0x13 - exponent as u8);
+                }
+                _ => {
+                    self.output.put_u8(0x14);
+                    self.output.put_u8(-exponent as u8);
+                }
+            }
+        }
+        self.put_decimal_significand(decimal.is_sign_negative(), &significand);
+        Ok(())
+    }
+
+    /// Serialize a decimal value that is known to be finite.
+    ///
+    /// This uses the same significand as [`serialize_decimal`](Self::serialize_decimal), but the
+    /// exponent of every `rust_decimal::Decimal` fits in the leading tag byte, so the encoding is
+    /// one byte shorter for magnitudes below 0.01 or of 10^20 and above. The encodings of the two
+    /// methods are not compatible with each other.
+    ///
+    /// # Example
+    /// ```
+    /// let d = "0.001".parse().unwrap();
+    /// let mut ser = memcomparable::Serializer::new(vec![]);
+    /// ser.serialize_finite_decimal(d).unwrap();
+    /// assert_eq!(ser.into_inner(), [0x8f, 0x14]);
+    /// ```
+    #[cfg(feature = "decimal")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
+    pub fn serialize_finite_decimal(&mut self, decimal: rust_decimal::Decimal) -> Result<()> {
+        if decimal.is_zero() {
+            self.output.put_u8(FINITE_DECIMAL_ZERO);
+            return Ok(());
+        }
+        let (exponent, significand) = Self::decimal_e_m(decimal);
+        let neg = decimal.is_sign_negative();
+        let tag = if neg {
+            FINITE_DECIMAL_NEG_BASE as i16 - exponent as i16
+        } else {
+            FINITE_DECIMAL_POS_BASE as i16 + exponent as i16
+        };
+        self.output.put_u8(tag as u8);
+        self.put_decimal_significand(neg, &significand);
+        Ok(())
+    }
+
+    #[cfg(feature = "decimal")]
+    fn put_decimal_significand(&mut self, neg: bool, significand: &[u8]) {
+        if neg {
+            for b in significand {
+                self.output.put_u8(!b);
+            }
+        } else {
+            self.output.put_slice(significand);
+        }
+    }
+
+    /// Get the exponent and significand mantissa from a decimal.
+    #[cfg(feature = "decimal")]
+    fn decimal_e_m(decimal: rust_decimal::Decimal) -> (i8, Vec<u8>) {
+        if decimal.is_zero() {
+            return (0, vec![]);
+        }
+        const POW10: [u128; 30] = [
             1,
             10,
             100,
@@ -572,7 +1503,7 @@ impl<B: BufMut> Serializer<B> {
 
         let mut byte_array = Vec::with_capacity(16);
         // Remove trailing zero.
-        while mantissa % 10 == 0 && mantissa != 0 {
+        while mantissa.is_multiple_of(10) && mantissa != 0 {
             mantissa /= 10;
             digit_num -= 1;
         }
@@ -618,12 +1549,289 @@ mod tests {
         assert_eq!(to_vec(&UnitStruct).unwrap(), []);
     }
 
+    #[test]
+    fn test_phantom_data() {
+        use std::marker::PhantomData;
+
+        assert_eq!(to_vec(&PhantomData::<u32>).unwrap(), []);
+        assert_eq!(to_vec(&[(); 3]).unwrap(), []);
+
+        #[derive(Serialize)]
+        struct Typed<T> {
+            id: u32,
+            _marker: PhantomData<T>,
+            name: &'static str,
+        }
+
+        #[derive(Serialize)]
+        struct Untyped {
+            id: u32,
+            name: &'static str,
+        }
+
+        let typed = Typed::<String> {
+            id: 7,
+            _marker: PhantomData,
+            name: "key",
+        };
+        let untyped = Untyped { id: 7, name: "key" };
+        assert_eq!(to_vec(&typed).unwrap(), to_vec(&untyped).unwrap());
+    }
+
+    #[test]
+    fn test_big_endian() {
+        assert_eq!(to_vec(&0x12u8).unwrap(), [0x12]);
+        assert_eq!(to_vec(&0x1234u16).unwrap(), [0x12, 0x34]);
+        assert_eq!(to_vec(&0x12345678u32).unwrap(), [0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(
+            to_vec(&0x0123_4567_89ab_cdefu64).unwrap(),
+            [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]
+        );
+        assert_eq!(
+            to_vec(&0x0011_2233_4455_6677_8899_aabb_ccdd_eeffu128).unwrap(),
+            [
+                0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+                0xee, 0xff
+            ]
+        );
+
+        // the order holds where a carry crosses into the next byte, which little-endian breaks
+        for shift in 0..63 {
+            let below = (1u64 << (shift + 1)) - 1;
+            let above = below + 1;
+            assert!(
+                to_vec(&below).unwrap() < to_vec(&above).unwrap(),
+                "{below:#x}"
+            );
+        }
+        assert!(to_vec(&0xffu64).unwrap() < to_vec(&0x100u64).unwrap());
+        assert!(to_vec(&0x01ffu16).unwrap() < to_vec(&0x0200u16).unwrap());
+    }
+
+    #[test]
+    fn test_signed_edges() {
+        assert_eq!(to_vec(&i128::MIN).unwrap(), [0; 16]);
+        assert_eq!(to_vec(&i128::MAX).unwrap(), [0xff; 16]);
+        let mut zero = [0; 16];
+        zero[0] = 0x80;
+        assert_eq!(to_vec(&0i128).unwrap(), zero);
+        let mut minus_one = [0xff; 16];
+        minus_one[0] = 0x7f;
+        assert_eq!(to_vec(&-1i128).unwrap(), minus_one);
+
+        macro_rules! check_order {
+            ($ty:ty) => {
+                let values = [
+                    <$ty>::MIN,
+                    <$ty>::MIN + 1,
+                    -1,
+                    0,
+                    1,
+                    <$ty>::MAX - 1,
+                    <$ty>::MAX,
+                ];
+                let encodings: Vec<_> = values.iter().map(|v| to_vec(v).unwrap()).collect();
+                assert!(encodings.windows(2).all(|w| w[0] < w[1]), stringify!($ty));
+            };
+        }
+        check_order!(i8);
+        check_order!(i16);
+        check_order!(i32);
+        check_order!(i64);
+        check_order!(i128);
+    }
+
+    #[test]
+    fn test_128_bit_order() {
+        // across the boundary of the lower 64-bit word, and at the extremes
+        let unsigned = [
+            0,
+            1,
+            u64::MAX as u128 - 1,
+            u64::MAX as u128,
+            u64::MAX as u128 + 1,
+            1 << 127,
+            u128::MAX - 1,
+            u128::MAX,
+        ];
+        let encodings: Vec<_> = unsigned.iter().map(|v| to_vec(v).unwrap()).collect();
+        assert!(encodings.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(to_vec(&(u64::MAX as u128 + 1)).unwrap()[7..9], [1, 0]);
+
+        let signed = [
+            i128::MIN,
+            i128::MIN + 1,
+            i64::MIN as i128 - 1,
+            i64::MIN as i128,
+            -(1 << 63) + 1,
+            -1,
+            0,
+            1,
+            i64::MAX as i128,
+            i64::MAX as i128 + 1,
+            u64::MAX as i128 + 1,
+            i128::MAX - 1,
+            i128::MAX,
+        ];
+        let encodings: Vec<_> = signed.iter().map(|v| to_vec(v).unwrap()).collect();
+        assert!(encodings.windows(2).all(|w| w[0] < w[1]));
+
+        for v in unsigned {
+            assert_eq!(crate::from_slice::<u128>(&to_vec(&v).unwrap()).unwrap(), v);
+        }
+        for (v, e) in signed.iter().zip(&encodings) {
+            assert_eq!(crate::from_slice::<i128>(e).unwrap(), *v);
+        }
+    }
+
     #[test]
     fn test_option() {
         assert_eq!(to_vec(&(None as Option<u8>)).unwrap(), [0]);
         assert_eq!(to_vec(&Some(0x12u8)).unwrap(), [1, 0x12]);
     }
 
+    #[test]
+    fn test_tagged_order() {
+        // declared in a different order than their stable tags
+        #[derive(Serialize)]
+        enum Event {
+            Updated(u32),
+            Created(u32),
+        }
+        impl Event {
+            fn tag(&self) -> u8 {
+                match self {
+                    Event::Created(_) => 1,
+                    Event::Updated(_) => 2,
+                }
+            }
+        }
+
+        let encode = |event: &Event| {
+            let mut ser = Serializer::new(vec![]);
+            let (Event::Created(id) | Event::Updated(id)) = event;
+            ser.serialize_tagged(event.tag(), id).unwrap();
+            ser.into_inner()
+        };
+        let (created, updated) = (Event::Created(9), Event::Updated(1));
+        assert!(encode(&created) < encode(&updated));
+        // serde's variant index orders them the other way
+        assert!(to_vec(&created).unwrap() > to_vec(&updated).unwrap());
+        assert!(encode(&Event::Created(1)) < encode(&created));
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn test_time_delta() {
+        use chrono::TimeDelta;
+
+        let deltas = [
+            TimeDelta::MIN,
+            TimeDelta::MIN + TimeDelta::nanoseconds(1),
+            TimeDelta::seconds(-1),
+            TimeDelta::milliseconds(-999),
+            TimeDelta::nanoseconds(-1),
+            TimeDelta::zero(),
+            TimeDelta::nanoseconds(1),
+            TimeDelta::milliseconds(999),
+            TimeDelta::seconds(1),
+            TimeDelta::MAX - TimeDelta::nanoseconds(1),
+            TimeDelta::MAX,
+        ];
+        let encode = |delta| {
+            let mut ser = Serializer::new(vec![]);
+            ser.serialize_time_delta(delta).unwrap();
+            ser.into_inner()
+        };
+        let encodings: Vec<_> = deltas.iter().map(|&d| encode(d)).collect();
+        assert!(encodings.windows(2).all(|w| w[0] < w[1]));
+
+        for (delta, bytes) in deltas.iter().zip(&encodings) {
+            let mut de = crate::Deserializer::new(bytes.as_slice());
+            assert_eq!(de.deserialize_time_delta().as_ref(), Ok(delta));
+            assert!(!de.has_remaining());
+        }
+        assert_eq!(
+            encode(TimeDelta::nanoseconds(-1)),
+            to_vec(&(-1i64, 999_999_999u32)).unwrap()
+        );
+
+        // out of range values are rejected
+        for invalid in [(0i64, 1_000_000_000u32), (i64::MAX, 0), (i64::MIN, 0)] {
+            let bytes = to_vec(&invalid).unwrap();
+            let mut de = crate::Deserializer::new(bytes.as_slice());
+            assert!(matches!(
+                de.deserialize_time_delta(),
+                Err(Error::At { position: 0, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_timestamp_order() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let encode = |time| {
+            let mut ser = Serializer::new(vec![]);
+            ser.serialize_timestamp_millis(crate::system_time_to_millis(time).unwrap())
+                .unwrap();
+            ser.serialize_timestamp_micros(crate::system_time_to_micros(time).unwrap())
+                .unwrap();
+            ser.into_inner()
+        };
+        let times = [
+            UNIX_EPOCH - Duration::from_secs(86400 * 365 * 100),
+            UNIX_EPOCH - Duration::from_millis(2),
+            UNIX_EPOCH - Duration::from_micros(1),
+            UNIX_EPOCH,
+            UNIX_EPOCH + Duration::from_micros(1),
+            UNIX_EPOCH + Duration::from_millis(1),
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        ];
+        for w in times.windows(2) {
+            assert!(encode(w[0]) < encode(w[1]), "{:?} vs {:?}", w[0], w[1]);
+        }
+        assert_eq!(encode(UNIX_EPOCH)[..8], to_vec(&0i64).unwrap());
+    }
+
+    #[test]
+    fn test_result_order() {
+        let values: [std::result::Result<u32, String>; 5] = [
+            Ok(0),
+            Ok(1),
+            Ok(u32::MAX),
+            Err(String::new()),
+            Err("error".to_string()),
+        ];
+        assert_eq!(to_vec(&values[1]).unwrap(), [0, 0, 0, 0, 1]);
+        assert_eq!(to_vec(&values[3]).unwrap(), [1, 0]);
+        for w in values.windows(2) {
+            assert!(to_vec(&w[0]).unwrap() < to_vec(&w[1]).unwrap(), "{w:?}");
+            assert!(
+                to_vec_desc(&w[0]).unwrap() > to_vec_desc(&w[1]).unwrap(),
+                "{w:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_option_order() {
+        let values = [None, Some(None), Some(Some(0u8)), Some(Some(0xff))];
+        assert_eq!(to_vec(&values[0]).unwrap(), [0]);
+        assert_eq!(to_vec(&values[1]).unwrap(), [1, 0]);
+        assert_eq!(to_vec(&values[2]).unwrap(), [1, 1, 0]);
+        for w in values.windows(2) {
+            assert!(to_vec(&w[0]).unwrap() < to_vec(&w[1]).unwrap());
+            assert!(to_vec_desc(&w[0]).unwrap() > to_vec_desc(&w[1]).unwrap());
+        }
+
+        // `None` sorts before any string, even an empty one
+        let values = [None, Some(""), Some("\0"), Some("a")];
+        for w in values.windows(2) {
+            assert!(to_vec(&w[0]).unwrap() < to_vec(&w[1]).unwrap());
+        }
+    }
+
     #[test]
     fn test_tuple() {
         let tuple: (i8, i16, i32, i64, i128) = (
@@ -660,16 +1868,262 @@ mod tests {
             ]
         );
 
-        #[derive(Serialize)]
-        struct NewTypeStruct(char);
-        let tuple = NewTypeStruct('G');
-        assert_eq!(to_vec(&tuple).unwrap(), [0, 0, 0, b'G']);
+        #[derive(Serialize)]
+        struct NewTypeStruct(char);
+        let tuple = NewTypeStruct('G');
+        assert_eq!(to_vec(&tuple).unwrap(), [0, 0, 0, b'G']);
+    }
+
+    #[test]
+    fn test_vec() {
+        let s: &[u8] = &[1, 2, 3];
+        assert_eq!(to_vec(&s).unwrap(), [1, 0x01, 1, 0x02, 1, 0x03, 0]);
+    }
+
+    #[test]
+    fn test_char_order() {
+        // around the ASCII boundary, the surrogate gap, and the end of the BMP
+        let chars = [
+            '\0',
+            '\u{7f}',
+            '\u{80}',
+            '\u{d7ff}',
+            '\u{e000}',
+            '\u{ffff}',
+            '\u{10000}',
+            char::MAX,
+        ];
+        for c in chars {
+            assert_eq!(to_vec(&c).unwrap(), (c as u32).to_be_bytes());
+        }
+        for w in chars.windows(2) {
+            assert!(to_vec(&w[0]).unwrap() < to_vec(&w[1]).unwrap());
+            assert!(to_vec_desc(&w[0]).unwrap() > to_vec_desc(&w[1]).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_finish() {
+        use serde::ser::{SerializeSeq, Serializer as _};
+
+        let mut ser = Serializer::new(vec![]);
+        vec![vec![1u8], vec![]].serialize(&mut ser).unwrap();
+        assert_eq!(ser.finish(), to_vec(&vec![vec![1u8], vec![]]));
+
+        // a sequence dropped without `end` lacks its terminator
+        let mut ser = Serializer::new(vec![]);
+        {
+            let mut seq = (&mut ser).serialize_seq(None).unwrap();
+            seq.serialize_element(&1u8).unwrap();
+        }
+        assert_eq!(ser.clone().finish(), Err(Error::IncompleteSequence));
+        assert_eq!(ser.into_inner(), [1, 1]);
+    }
+
+    #[test]
+    fn test_seq_framing() {
+        fn encode(v: &impl Serialize) -> Vec<u8> {
+            let mut ser = Serializer::new(vec![]);
+            ser.set_seq_framing(SeqFraming::LengthPrefixed);
+            v.serialize(&mut ser).unwrap();
+            ser.into_inner()
+        }
+
+        assert_eq!(to_vec(&vec![1u8, 2]).unwrap(), [1, 1, 1, 2, 0]);
+        assert_eq!(encode(&vec![1u8, 2]), [0, 0, 0, 2, 1, 2]);
+        assert_eq!(encode(&Vec::<u8>::new()), [0, 0, 0, 0]);
+        assert_eq!(
+            encode(&vec![vec![1u8], vec![]]),
+            [0, 0, 0, 2, 0, 0, 0, 1, 1, 0, 0, 0, 0]
+        );
+        // tuples and strings are unaffected
+        assert_eq!(encode(&(1u8, "a")), to_vec(&(1u8, "a")).unwrap());
+
+        // the length hint must match the number of elements
+        struct Lying(usize);
+        impl Serialize for Lying {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                use serde::ser::SerializeSeq;
+                let mut seq = serializer.serialize_seq(Some(self.0))?;
+                seq.serialize_element(&1u8)?;
+                seq.end()
+            }
+        }
+        let mut ser = Serializer::new(vec![]);
+        ser.set_seq_framing(SeqFraming::LengthPrefixed);
+        assert_eq!(
+            Lying(2).serialize(&mut ser),
+            Err(Error::SeqLengthMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(ser.finish(), Ok(vec![0, 0, 0, 2, 1]));
+        assert_eq!(encode(&Lying(1)), [0, 0, 0, 1, 1]);
+
+        // sequences of the same length still compare element by element
+        assert!(encode(&vec![1u8, 2]) < encode(&vec![1u8, 3]));
+        // but a shorter sequence sorts first regardless of its elements
+        assert!(vec![1u8, 1] < vec![2u8]);
+        assert!(encode(&vec![1u8, 1]) > encode(&vec![2u8]));
+    }
+
+    #[test]
+    fn test_vec_order() {
+        fn encode(v: &Vec<u8>, reverse: bool) -> Vec<u8> {
+            let mut ser = Serializer::new(vec![]);
+            ser.set_reverse(reverse);
+            v.serialize(&mut ser).unwrap();
+            ser.into_inner()
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut rand_vec = || -> Vec<u8> {
+            let len = rng.gen_range(0..4);
+            (0..len).map(|_| rng.gen_range(0..3)).collect()
+        };
+        for _ in 0..1000 {
+            let (a, b) = (rand_vec(), rand_vec());
+            assert_eq!(a.cmp(&b), encode(&a, false).cmp(&encode(&b, false)));
+            assert_eq!(a.cmp(&b), encode(&a, true).cmp(&encode(&b, true)).reverse());
+        }
+
+        // an empty sequence sorts first, or last in reverse order
+        let (empty, zero) = (vec![], vec![0]);
+        assert!(encode(&empty, false) < encode(&zero, false));
+        assert!(encode(&empty, true) > encode(&zero, true));
+    }
+
+    #[test]
+    fn test_array_order() {
+        // arrays are tuples, packed without framing
+        let a = [0x0102_0304u32, 5, 6, 7];
+        let bytes = to_vec(&a).unwrap();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(bytes[..8], [1, 2, 3, 4, 0, 0, 0, 5]);
+        assert_eq!(crate::from_slice::<[u32; 4]>(&bytes), Ok(a));
+
+        let mut rng = rand::thread_rng();
+        // draw from a small range so that arrays often share a prefix
+        let mut rand_array = || -> [u32; 4] { std::array::from_fn(|_| rng.gen_range(0..3) << 16) };
+        for _ in 0..1000 {
+            let (a, b) = (rand_array(), rand_array());
+            assert_eq!(a.cmp(&b), to_vec(&a).unwrap().cmp(&to_vec(&b).unwrap()));
+        }
+        let (a, b) = ([-1i16, i16::MAX], [0i16, i16::MIN]);
+        assert!(to_vec(&a).unwrap() < to_vec(&b).unwrap());
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let value = (
+            42u32,
+            "a value longer than a chunk",
+            Some(-1.5f64),
+            vec!['x'],
+        );
+        assert_eq!(to_bytes(&value).unwrap(), to_vec(&value).unwrap());
+        assert_eq!(to_bytes(&()).unwrap(), Bytes::new());
+        assert_eq!(
+            to_bytes(&std::collections::BTreeMap::<u8, u8>::new()),
+            Err(Error::NotSupported("map"))
+        );
+    }
+
+    #[test]
+    fn test_to_slice() {
+        let value = (0x1234_5678u32, -2i64);
+        let mut buf = [0u8; 12];
+        assert_eq!(to_slice(&value, &mut buf), Ok(12));
+        assert_eq!(buf[..], to_vec(&value).unwrap());
+
+        let mut buf = [0u8; 16];
+        assert_eq!(to_slice(&"abc", &mut buf), Ok(10));
+        assert_eq!(buf[..10], to_vec(&"abc").unwrap());
+
+        // the full length is reported on overflow, also when a write straddles the end
+        let mut buf = [0u8; 11];
+        assert_eq!(
+            to_slice(&value, &mut buf),
+            Err(Error::BufferTooSmall { needed: 12 })
+        );
+        assert_eq!(
+            to_slice(&"a string of 3 chunks", &mut buf),
+            Err(Error::BufferTooSmall { needed: 28 })
+        );
+        assert_eq!(to_slice(&(), &mut []), Ok(0));
+    }
+
+    #[test]
+    fn test_compare_by_encoding() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let a = (rng.gen_range(-2i32..2), rand_string(0..12));
+            let b = (rng.gen_range(-2i32..2), rand_string(0..12));
+            assert_eq!(compare_by_encoding(&a, &b), Ok(a.cmp(&b)));
+        }
+        assert_eq!(compare_by_encoding("a", "a"), Ok(Ordering::Equal));
+
+        // a value that compares other values while being serialized
+        struct Nested;
+        impl Serialize for Nested {
+            fn serialize<S: ser::Serializer>(&self, s: S) -> std::result::Result<S::Ok, S::Error> {
+                let ord = compare_by_encoding(&1u8, &2u8).unwrap();
+                s.serialize_i8(ord as i8)
+            }
+        }
+        assert_eq!(compare_by_encoding(&Nested, &Nested), Ok(Ordering::Equal));
+
+        let map = std::collections::BTreeMap::from([(1u8, 1u8)]);
+        assert!(compare_by_encoding(&map, &map).is_err());
     }
 
     #[test]
-    fn test_vec() {
-        let s: &[u8] = &[1, 2, 3];
-        assert_eq!(to_vec(&s).unwrap(), [1, 0x01, 1, 0x02, 1, 0x03, 0]);
+    fn test_to_vec_desc() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let a: (i32, String) = (rng.gen_range(-2..2), rand_string(0..3));
+            let b: (i32, String) = (rng.gen_range(-2..2), rand_string(0..3));
+            let (ea, eb) = (to_vec_desc(&a).unwrap(), to_vec_desc(&b).unwrap());
+            assert_eq!(a.cmp(&b), eb.cmp(&ea), "{a:?} vs {b:?}");
+        }
+        let expected: Vec<u8> = to_vec(&1u16).unwrap().iter().map(|b| !b).collect();
+        assert_eq!(to_vec_desc(&1u16).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_vec_of_wrappers() {
+        // element marker, then option tag, then value
+        let v = vec![Some(1u8), None];
+        assert_eq!(to_vec(&v).unwrap(), [1, 1, 0x01, 1, 0, 0]);
+        assert_eq!(to_vec(&vec![Some(None::<u8>)]).unwrap(), [1, 1, 0, 0]);
+
+        // references and smart pointers are transparent
+        let values = ["a", "bc"];
+        let expected = to_vec(&values.to_vec()).unwrap();
+        let boxed: Vec<Box<str>> = values.iter().map(|&s| s.into()).collect();
+        let rc: Vec<std::rc::Rc<str>> = values.iter().map(|&s| s.into()).collect();
+        let refs: Vec<&&str> = values.iter().collect();
+        assert_eq!(to_vec(&boxed).unwrap(), expected);
+        assert_eq!(to_vec(&rc).unwrap(), expected);
+        assert_eq!(to_vec(&refs.as_slice()).unwrap(), expected);
+
+        // the framing preserves the order of nested options
+        let mut vecs = [
+            vec![],
+            vec![None],
+            vec![None, None],
+            vec![None, Some(0u8)],
+            vec![Some(0)],
+            vec![Some(0), None],
+            vec![Some(1)],
+        ];
+        vecs.sort();
+        let encodings: Vec<_> = vecs.iter().map(|v| to_vec(v).unwrap()).collect();
+        assert!(encodings.windows(2).all(|w| w[0] < w[1]));
     }
 
     #[test]
@@ -761,17 +2215,52 @@ mod tests {
                 2
             ]
         );
+        assert_eq!(
+            to_vec(&"1234567812345678").unwrap(),
+            [
+                1, b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'8', 9, b'1', b'2', b'3', b'4', b'5',
+                b'6', b'7', b'8', 8
+            ]
+        );
+
+        // the reversed encoding flips every byte
+        let mut ser = Serializer::new(vec![]);
+        ser.set_reverse(true);
+        "1234567890".serialize(&mut ser).unwrap();
+        let expected = to_vec(&"1234567890").unwrap();
+        assert_eq!(
+            ser.into_inner(),
+            expected.iter().map(|b| !b).collect::<Vec<_>>()
+        );
     }
 
     #[test]
-    fn test_string_order() {
-        fn to_vec_desc(s: &str) -> Vec<u8> {
-            let mut ser = Serializer::new(vec![]);
-            ser.set_reverse(true);
-            s.serialize(&mut ser).unwrap();
-            ser.into_inner()
+    fn test_str_and_string() {
+        assert_eq!(
+            to_vec(&"hello").unwrap(),
+            to_vec(&"hello".to_string()).unwrap()
+        );
+        assert_eq!(
+            to_vec(&std::borrow::Cow::Borrowed("hello")).unwrap(),
+            to_vec(&Box::<str>::from("hello")).unwrap()
+        );
+
+        // multi-byte characters are encoded as their UTF-8 bytes
+        let crab = "\u{1f980}";
+        assert_eq!(
+            to_vec(&crab).unwrap(),
+            [1, 0xf0, 0x9f, 0xa6, 0x80, 0, 0, 0, 0, 4]
+        );
+        // and sort by byte value, which is also code point order
+        let strings = ["a", "z", "\u{e9}", "\u{4e2d}", "\u{ff5e}", crab];
+        for w in strings.windows(2) {
+            assert!(w[0] < w[1]);
+            assert!(to_vec(&w[0]).unwrap() < to_vec(&w[1]).unwrap(), "{w:?}");
         }
+    }
 
+    #[test]
+    fn test_string_order() {
         for _ in 0..1000 {
             let s = rand_string(0..16);
             let a = s.clone() + &rand_string(0..16);
@@ -781,12 +2270,72 @@ mod tests {
             let eb = to_vec(&b).unwrap();
             assert_eq!(a.cmp(&b), ea.cmp(&eb));
 
-            let ra = to_vec_desc(&a);
-            let rb = to_vec_desc(&b);
+            let ra = to_vec_desc(&a).unwrap();
+            let rb = to_vec_desc(&b).unwrap();
             assert_eq!(a.cmp(&b), ra.cmp(&rb).reverse());
         }
     }
 
+    #[test]
+    fn test_variable_fields() {
+        fn check<T>(values: &[T])
+        where
+            T: Serialize + serde::de::DeserializeOwned + Ord + std::fmt::Debug,
+        {
+            for a in values {
+                let ea = to_vec(a).unwrap();
+                assert_eq!(&crate::from_slice::<T>(&ea).unwrap(), a);
+                for b in values {
+                    let eb = to_vec(b).unwrap();
+                    assert_eq!(a.cmp(b), ea.cmp(&eb), "{a:?} {b:?}");
+                }
+            }
+        }
+
+        // strings around the chunk size, including ones ending in zeros like the padding
+        let strings = [
+            "",
+            "\0",
+            "a",
+            "a\0",
+            "a\0\0",
+            "abcdefg",
+            "abcdefgh",
+            "abcdefgh\0",
+            "abcdefghi",
+            "b",
+        ];
+        let mut pairs = vec![];
+        for s in strings {
+            for n in [0, 1, u32::MAX] {
+                pairs.push((s.to_string(), n));
+            }
+        }
+        check(&pairs);
+
+        let mut triples = vec![];
+        for n in [0u32, 7] {
+            for s in strings {
+                for i in [i64::MIN, 0, i64::MAX] {
+                    triples.push((n, s.to_string(), i));
+                }
+            }
+        }
+        check(&triples);
+
+        let vecs: Vec<Vec<u8>> = [&[][..], &[0], &[0, 0], &[1], &[1, 0], &[255]]
+            .iter()
+            .map(|v| v.to_vec())
+            .collect();
+        let mut vec_pairs = vec![];
+        for a in &vecs {
+            for b in &vecs {
+                vec_pairs.push((a.clone(), b.clone()));
+            }
+        }
+        check(&vec_pairs);
+    }
+
     fn rand_string(len_range: std::ops::Range<usize>) -> String {
         let mut rng = rand::thread_rng();
         let len = rng.gen_range(len_range);
@@ -814,6 +2363,113 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_float_infinity_order() {
+        macro_rules! check {
+            ($ty:ident) => {
+                let values = [
+                    $ty::NEG_INFINITY,
+                    $ty::MIN,
+                    -1.0,
+                    -0.0,
+                    0.0,
+                    1.0,
+                    $ty::MAX,
+                    $ty::INFINITY,
+                    $ty::NAN,
+                ];
+                for w in values.windows(2) {
+                    let (a, b) = (to_vec(&w[0]).unwrap(), to_vec(&w[1]).unwrap());
+                    // `-0.0` is encoded as `0.0`
+                    if w[0] == 0.0 && w[1] == 0.0 {
+                        assert_eq!(a, b);
+                    } else {
+                        assert!(a < b, "{} vs {}", w[0], w[1]);
+                    }
+                }
+            };
+        }
+        check!(f32);
+        check!(f64);
+
+        assert_eq!(
+            to_vec(&f64::NEG_INFINITY).unwrap(),
+            [0x00, 0x0f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        );
+        assert_eq!(
+            to_vec(&f64::INFINITY).unwrap(),
+            [0xff, 0xf0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            to_vec(&f32::NEG_INFINITY).unwrap(),
+            [0x00, 0x7f, 0xff, 0xff]
+        );
+        assert_eq!(to_vec(&f32::INFINITY).unwrap(), [0xff, 0x80, 0, 0]);
+    }
+
+    #[test]
+    fn test_nan_order() {
+        fn encode<T: Serialize>(v: T, nan_order: NanOrder, reverse: bool) -> Vec<u8> {
+            let mut ser = Serializer::new(vec![]);
+            ser.set_nan_order(nan_order);
+            ser.set_reverse(reverse);
+            v.serialize(&mut ser).unwrap();
+            ser.into_inner()
+        }
+
+        for reverse in [false, true] {
+            let order = |a: Vec<u8>, b: Vec<u8>| if reverse { b < a } else { a < b };
+            for nan in [f64::NAN, -f64::NAN] {
+                let (neg_inf, inf) = (f64::NEG_INFINITY, f64::INFINITY);
+                let high = |v| encode(v, NanOrder::High, reverse);
+                assert!(order(high(neg_inf), high(inf)));
+                assert!(order(high(inf), high(nan)));
+                let low = |v| encode(v, NanOrder::Low, reverse);
+                assert!(order(low(nan), low(neg_inf)));
+                assert!(order(low(neg_inf), low(inf)));
+            }
+            let (nan, neg_inf) = (f32::NAN, f32::NEG_INFINITY);
+            assert!(order(
+                encode(nan, NanOrder::Low, reverse),
+                encode(neg_inf, NanOrder::Low, reverse)
+            ));
+            assert!(order(
+                encode(neg_inf, NanOrder::High, reverse),
+                encode(nan, NanOrder::High, reverse)
+            ));
+        }
+        // other values are unaffected
+        assert_eq!(
+            encode(1.5f64, NanOrder::Low, false),
+            to_vec(&1.5f64).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_float_zero_and_subnormal() {
+        assert_eq!(to_vec(&-0.0f64).unwrap(), to_vec(&0.0f64).unwrap());
+        assert_eq!(to_vec(&-0.0f32).unwrap(), to_vec(&0.0f32).unwrap());
+
+        // the smallest subnormals are the immediate neighbours of zero
+        let key = |v: f64| u64::from_be_bytes(to_vec(&v).unwrap().try_into().unwrap());
+        let min_subnormal = f64::from_bits(1);
+        assert_eq!(key(min_subnormal), key(0.0) + 1);
+        // the code in between would be `-0.0`, which is normalized away
+        assert_eq!(key(-min_subnormal), key(0.0) - 2);
+
+        let max_subnormal = f64::from_bits((1 << 52) - 1);
+        let values = [
+            -f64::MIN_POSITIVE,
+            -max_subnormal,
+            -min_subnormal,
+            0.0,
+            min_subnormal,
+            max_subnormal,
+            f64::MIN_POSITIVE,
+        ];
+        assert!(values.windows(2).all(|w| key(w[0]) < key(w[1])));
+    }
+
     #[test]
     #[cfg(feature = "decimal")]
     fn test_decimal_e_m() {
@@ -868,6 +2524,390 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_clone() {
+        #[derive(Serialize)]
+        struct Test {
+            a: u8,
+            b: u16,
+        }
+
+        let mut ser = Serializer::new(vec![]);
+        0x12u8.serialize(&mut ser).unwrap();
+        let mut checkpoint = ser.clone();
+        ser.set_reverse(true);
+        0x3456u16.serialize(&mut ser).unwrap();
+        0x789au16.serialize(&mut checkpoint).unwrap();
+
+        assert_eq!(ser.into_inner(), [0x12, !0x34, !0x56]);
+        assert_eq!(
+            checkpoint.into_inner(),
+            to_vec(&Test { a: 0x12, b: 0x789a }).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_human_readable() {
+        let addr = std::net::Ipv4Addr::new(127, 0, 0, 1);
+        assert_eq!(to_vec(&addr).unwrap(), [127, 0, 0, 1]);
+
+        let mut ser = Serializer::new(vec![]);
+        ser.set_human_readable(true);
+        addr.serialize(&mut ser).unwrap();
+        assert_eq!(ser.into_inner(), to_vec(&"127.0.0.1").unwrap());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    async fn test_async_writer() {
+        let value = (1u32, "hello", Some(-1i64));
+        let mut writer = vec![];
+        to_async_writer(&mut writer, &value).await.unwrap();
+        to_async_writer(&mut writer, &value).await.unwrap();
+        assert_eq!(writer, to_vec(&(value, value)).unwrap());
+    }
+
+    #[test]
+    fn test_batch() {
+        let values = ["", "a", "12345678", "123456789"];
+        let (bytes, offsets) = to_vec_batch(&values).unwrap();
+        assert_eq!(offsets.len(), values.len() + 1);
+        assert_eq!(*offsets.last().unwrap(), bytes.len());
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(bytes[offsets[i]..offsets[i + 1]], to_vec(value).unwrap());
+        }
+
+        let (bytes, offsets) = to_vec_batch::<u8>(&[]).unwrap();
+        assert!(bytes.is_empty());
+        assert_eq!(offsets, [0]);
+    }
+
+    #[test]
+    fn test_put_encoded() {
+        let a = (0x12u8, "hello");
+        let b = Some(-1i32);
+
+        let mut ser = Serializer::new(vec![]);
+        ser.put_encoded(&to_vec(&a).unwrap());
+        b.serialize(&mut ser).unwrap();
+        assert_eq!(ser.into_inner(), to_vec(&(a, b)).unwrap());
+
+        let mut ser = Serializer::new(vec![]);
+        ser.set_reverse(true);
+        ser.put_encoded(&to_vec(&a).unwrap());
+        let mut expected = Serializer::new(vec![]);
+        expected.set_reverse(true);
+        a.serialize(&mut expected).unwrap();
+        assert_eq!(ser.into_inner(), expected.into_inner());
+    }
+
+    #[test]
+    fn test_fixed_bytes() {
+        fn encode(bytes: &[u8], reverse: bool) -> Vec<u8> {
+            let mut ser = Serializer::new(vec![]);
+            ser.set_reverse(reverse);
+            ser.serialize_fixed_bytes(bytes).unwrap();
+            ser.into_inner()
+        }
+
+        assert_eq!(encode(&[0; 16], false), [0; 16]);
+        assert_eq!(encode(&[0xff; 32], false), [0xff; 32]);
+        assert_eq!(encode(&[0xff; 32], true), [0; 32]);
+
+        let mut rng = rand::thread_rng();
+        for len in [16, 32] {
+            let mut arrays = vec![vec![0; len], vec![0xff; len]];
+            for _ in 0..100 {
+                arrays.push((0..len).map(|_| rng.gen()).collect());
+            }
+            for a in &arrays {
+                for b in &arrays {
+                    assert_eq!(a.cmp(b), encode(a, false).cmp(&encode(b, false)));
+                    assert_eq!(a.cmp(b), encode(a, true).cmp(&encode(b, true)).reverse());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_str_order() {
+        fn encode(s: &str, reverse: bool) -> Vec<u8> {
+            let mut ser = Serializer::new(vec![]);
+            ser.set_reverse(reverse);
+            ser.serialize_fixed_str(s, 8).unwrap();
+            ser.into_inner()
+        }
+
+        let strings = ["", "a", "a\u{1}", "ab", "abcdefgh", "b", "é"];
+        for a in strings {
+            assert_eq!(encode(a, false).len(), 8);
+            for b in strings {
+                assert_eq!(a.cmp(b), encode(a, false).cmp(&encode(b, false)), "{a} {b}");
+                assert_eq!(a.cmp(b), encode(a, true).cmp(&encode(b, true)).reverse());
+            }
+        }
+
+        let mut ser = Serializer::new(vec![]);
+        assert_eq!(
+            ser.serialize_fixed_str("abcdefghi", 8),
+            Err(Error::LengthLimitExceeded { limit: 8 })
+        );
+    }
+
+    #[test]
+    fn test_bool_slice_order() {
+        fn encode(bits: &[bool], reverse: bool) -> Vec<u8> {
+            let mut ser = Serializer::new(vec![]);
+            ser.set_reverse(reverse);
+            ser.serialize_bool_slice(bits).unwrap();
+            ser.into_inner()
+        }
+
+        let mut rng = rand::thread_rng();
+        for len in [1, 7, 8, 9, 20] {
+            let slices: Vec<Vec<bool>> = (0..50)
+                .map(|_| (0..len).map(|_| rng.gen()).collect())
+                .collect();
+            for a in &slices {
+                for b in &slices {
+                    assert_eq!(a.cmp(b), encode(a, false).cmp(&encode(b, false)));
+                    assert_eq!(a.cmp(b), encode(a, true).cmp(&encode(b, true)).reverse());
+                }
+            }
+        }
+
+        // a byte per 8 bools, against 2 bytes per bool plus 1 for a `Vec<bool>`
+        let bits = vec![true; 100];
+        assert_eq!(encode(&bits, false).len(), 13);
+        assert_eq!(to_vec(&bits).unwrap().len(), 201);
+        assert_eq!(encode(&[], false), []);
+    }
+
+    #[test]
+    fn test_sorted_map() {
+        fn encode(map: &HashMap<String, u32>) -> Vec<u8> {
+            let mut ser = Serializer::new(vec![]);
+            ser.serialize_sorted_map(map).unwrap();
+            ser.into_inner()
+        }
+
+        let entries: Vec<_> = (0..100).map(|i| (format!("key{i}"), i)).collect();
+        let map1: HashMap<_, _> = entries.iter().cloned().collect();
+        let map2: HashMap<_, _> = entries.iter().rev().cloned().collect();
+        assert_eq!(encode(&map1), encode(&map2));
+        assert_eq!(encode(&HashMap::new()), [0]);
+
+        let mut sorted = entries;
+        sorted.sort();
+        assert_eq!(encode(&map1), to_vec(&sorted).unwrap());
+    }
+
+    #[test]
+    fn test_entries() {
+        let entries = [
+            ("a".to_string(), 1i32),
+            ("b".to_string(), -1),
+            ("c".to_string(), 0),
+        ];
+        for seq_framing in [SeqFraming::Marker, SeqFraming::LengthPrefixed] {
+            let mut ser = Serializer::new(vec![]);
+            ser.set_seq_framing(seq_framing);
+            ser.serialize_entries(entries.iter().cloned()).unwrap();
+            let bytes = ser.into_inner();
+
+            let mut de = crate::Deserializer::new(bytes.as_slice());
+            de.set_seq_framing(seq_framing);
+            let decoded: Vec<(String, i32)> = serde::Deserialize::deserialize(&mut de).unwrap();
+            assert_eq!(decoded, entries);
+        }
+
+        let mut ser = Serializer::new(vec![]);
+        ser.serialize_entries(std::iter::empty::<(u8, u8)>())
+            .unwrap();
+        assert_eq!(ser.into_inner(), [0]);
+
+        // keys must be strictly ascending
+        for keys in [[2u8, 1], [1, 1]] {
+            let mut ser = Serializer::new(vec![]);
+            assert_eq!(
+                ser.serialize_entries(keys.into_iter().zip(0u8..)),
+                Err(Error::Message("entries are not sorted by key".into()))
+            );
+            // the rejected sequence does not stay open
+            assert!(ser.finish().is_ok());
+        }
+
+        let map = std::collections::BTreeMap::from([(1u8, 2u8)]);
+        assert_eq!(to_vec(&map), Err(Error::NotSupported("map")));
+    }
+
+    #[test]
+    fn test_nullable_str_order() {
+        fn encode(v: Option<&str>, reverse: bool) -> Vec<u8> {
+            let mut ser = Serializer::new(vec![]);
+            ser.set_reverse(reverse);
+            ser.serialize_nullable_str(v).unwrap();
+            ser.into_inner()
+        }
+
+        let values = [
+            None,
+            Some(""),
+            Some("\0"),
+            Some("a"),
+            Some("a longer string"),
+        ];
+        for w in values.windows(2) {
+            assert!(encode(w[0], false) < encode(w[1], false), "{w:?}");
+            assert!(encode(w[0], true) > encode(w[1], true), "{w:?}");
+        }
+        // the chunks are the same as those of a string
+        assert_eq!(encode(Some("a"), false)[1..], to_vec(&"a").unwrap()[1..]);
+    }
+
+    #[test]
+    fn test_nullable_order() {
+        fn encode(v: Option<u32>, nulls_last: bool, reverse: bool) -> Vec<u8> {
+            let mut ser = Serializer::new(vec![]);
+            ser.set_reverse(reverse);
+            ser.serialize_nullable(v.as_ref(), nulls_last).unwrap();
+            ser.into_inner()
+        }
+
+        for v in [0, 1, u32::MAX] {
+            assert!(encode(None, false, false) < encode(Some(v), false, false));
+            assert!(encode(None, true, false) > encode(Some(v), true, false));
+            assert!(encode(None, false, true) > encode(Some(v), false, true));
+            assert!(encode(None, true, true) < encode(Some(v), true, true));
+        }
+        assert!(encode(Some(1), true, false) < encode(Some(2), true, false));
+        assert_eq!(encode(None, true, false), [0xff]);
+        // nulls first is the same as an option
+        for v in [None, Some(7)] {
+            assert_eq!(encode(v, false, false), to_vec(&v).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_can_serialize() {
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize)]
+        struct Plain {
+            id: u32,
+            tags: Vec<String>,
+            score: Option<f64>,
+        }
+
+        #[derive(Serialize)]
+        struct WithMap {
+            id: u32,
+            attrs: BTreeMap<String, String>,
+        }
+
+        let plain = Plain {
+            id: 1,
+            tags: vec!["a".into()],
+            score: None,
+        };
+        assert_eq!(can_serialize(&plain), Ok(()));
+        let with_map = WithMap {
+            id: 1,
+            attrs: BTreeMap::new(),
+        };
+        assert_eq!(can_serialize(&with_map), Err(Error::NotSupported("map")));
+    }
+
+    #[test]
+    fn test_sorted_set() {
+        use std::collections::BTreeSet;
+
+        fn encode(set: &HashSet<String>) -> Vec<u8> {
+            let mut ser = Serializer::new(vec![]);
+            ser.serialize_sorted_set(set).unwrap();
+            ser.into_inner()
+        }
+
+        let elements: Vec<_> = (0..100).map(|i| format!("tag{i}")).collect();
+        let set1: HashSet<_> = elements.iter().cloned().collect();
+        let set2: HashSet<_> = elements.iter().rev().cloned().collect();
+        assert_eq!(encode(&set1), encode(&set2));
+        assert_eq!(encode(&HashSet::new()), [0]);
+
+        let sorted: BTreeSet<_> = elements.into_iter().collect();
+        assert_eq!(encode(&set1), to_vec(&sorted).unwrap());
+    }
+
+    #[test]
+    fn test_bytes_from_reader() {
+        for len in [0, 1, 7, 8, 9, 16, 17, 1000] {
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            for reverse in [false, true] {
+                let mut ser = Serializer::new(vec![]);
+                ser.set_reverse(reverse);
+                ser.serialize_bytes_from_reader(std::io::Cursor::new(&data), len)
+                    .unwrap();
+                let mut expected = Serializer::new(vec![]);
+                expected.set_reverse(reverse);
+                ser::Serializer::serialize_bytes(&mut expected, &data).unwrap();
+                assert_eq!(ser.into_inner(), expected.into_inner(), "len {len}");
+            }
+        }
+
+        // the reader ends early
+        let mut ser = Serializer::new(vec![]);
+        let err = ser
+            .serialize_bytes_from_reader(&[1u8, 2, 3][..], 4)
+            .unwrap_err();
+        assert!(matches!(err, Error::Io(_)), "{err}");
+    }
+
+    #[test]
+    fn test_socket_addr() {
+        fn encode(addr: SocketAddr) -> Vec<u8> {
+            let mut ser = Serializer::new(vec![]);
+            ser.serialize_socket_addr(addr).unwrap();
+            ser.into_inner()
+        }
+
+        let mut addrs: Vec<SocketAddr> = [
+            "0.0.0.0:0",
+            "10.0.0.1:80",
+            "10.0.0.1:8080",
+            "10.0.0.2:1",
+            "255.255.255.255:65535",
+            "[::]:0",
+            "[::1]:443",
+            "[::ffff:10.0.0.1]:80",
+            "[fe80::1]:22",
+            "[fe80::1]:80",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        addrs.sort();
+        for pair in addrs.windows(2) {
+            assert!(encode(pair[0]) < encode(pair[1]), "{pair:?}");
+        }
+        for addr in addrs {
+            assert_eq!(encode(addr), to_vec(&addr).unwrap());
+        }
+
+        // flowinfo and scope_id are ignored
+        let v6 = std::net::SocketAddrV6::new("fe80::1".parse().unwrap(), 80, 7, 3);
+        assert_eq!(encode(v6.into()), encode("[fe80::1]:80".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_framed() {
+        let mut ser = Serializer::new(vec![]);
+        ser.serialize_framed(&(0x12u8, "a")).unwrap();
+        assert_eq!(
+            ser.into_inner(),
+            [0, 0, 0, 11, 0x12, 1, b'a', 0, 0, 0, 0, 0, 0, 0, 1]
+        );
+    }
+
     #[test]
     fn test_reverse_order() {
         // Order: (ASC, DESC)
@@ -885,4 +2925,103 @@ mod tests {
         assert!(serialize(v1) < serialize(v2));
         assert!(serialize(v2) < serialize(v3));
     }
+
+    #[test]
+    fn test_option_reverse() {
+        fn check<T>(values: &[T])
+        where
+            T: Ord + std::fmt::Debug + Serialize + for<'de> serde::Deserialize<'de>,
+        {
+            for a in values {
+                let (asc, desc) = (to_vec(a).unwrap(), to_vec_desc(a).unwrap());
+                assert_eq!(crate::from_slice::<T>(&asc).as_ref(), Ok(a));
+                assert_eq!(crate::from_slice_desc::<T>(&desc).as_ref(), Ok(a));
+                for b in values {
+                    let ord = a.cmp(b);
+                    assert_eq!(asc.cmp(&to_vec(b).unwrap()), ord, "{a:?} {b:?}");
+                    assert_eq!(desc.cmp(&to_vec_desc(b).unwrap()), ord.reverse());
+                }
+            }
+        }
+
+        check(&[
+            None,
+            Some((0u8, i32::MIN)),
+            Some((0, -1)),
+            Some((0, 0)),
+            Some((1, -5)),
+            Some((u8::MAX, i32::MAX)),
+        ]);
+        check(&[
+            None,
+            Some(String::new()),
+            Some("a".to_string()),
+            Some("a\0".to_string()),
+            Some("ab".to_string()),
+            Some("b".repeat(20)),
+        ]);
+        check(&[None, Some(None), Some(Some((1u8, 2i32)))]);
+    }
+
+    #[test]
+    fn test_checked_floats() {
+        for v in [
+            0.0,
+            -0.0,
+            1.5,
+            -1e300,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::MIN_POSITIVE,
+        ] {
+            let mut ser = Serializer::new(vec![]);
+            ser.serialize_f64_checked(v).unwrap();
+            ser.serialize_f32_checked(v as f32).unwrap();
+            assert_eq!(ser.into_inner(), to_vec(&(v, v as f32)).unwrap());
+        }
+
+        let mut ser = Serializer::new(vec![]);
+        ser.set_nan_order(NanOrder::Low);
+        assert_eq!(
+            ser.serialize_f64_checked(f64::NAN),
+            Err(Error::NanNotAllowed)
+        );
+        assert_eq!(
+            ser.serialize_f64_checked(-f64::NAN),
+            Err(Error::NanNotAllowed)
+        );
+        assert_eq!(
+            ser.serialize_f32_checked(f32::NAN),
+            Err(Error::NanNotAllowed)
+        );
+        // nothing is written for a rejected NaN
+        assert!(ser.into_inner().is_empty());
+    }
+
+    #[test]
+    fn test_empty_bytes_reverse() {
+        assert_eq!(to_vec(&"").unwrap(), [0]);
+        assert_eq!(to_vec_desc(&"").unwrap(), [0xff]);
+
+        // the empty marker sorts first ascending and last descending, even against strings
+        // starting with 0xff or made of zeros
+        let values = [
+            "".to_string(),
+            "\0".to_string(),
+            "a".to_string(),
+            "\u{ff}".to_string(),
+            "\u{10ffff}".repeat(3),
+        ];
+        for w in values.windows(2) {
+            assert!(to_vec(&w[0]).unwrap() < to_vec(&w[1]).unwrap());
+            assert!(to_vec_desc(&w[0]).unwrap() > to_vec_desc(&w[1]).unwrap());
+        }
+        for bytes in [&[][..], &[0], &[0xff; 9]] {
+            let desc = to_vec_desc(&serde_bytes::Bytes::new(bytes)).unwrap();
+            let decoded: serde_bytes::ByteBuf = crate::from_slice_desc(&desc).unwrap();
+            assert_eq!(decoded.as_slice(), bytes);
+            assert!(desc <= to_vec_desc(&"").unwrap());
+        }
+        assert_eq!(crate::from_slice_desc::<String>(&[0xff]).unwrap(), "");
+    }
 }
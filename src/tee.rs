@@ -0,0 +1,129 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::buf::UninitSlice;
+use bytes::BufMut;
+
+/// Size of the buffer handed out by `chunk_mut`.
+const STAGING_SIZE: usize = 64;
+
+/// A `BufMut` that writes everything to two buffers.
+///
+/// This allows serializing a value into two sinks at once.
+///
+/// # Example
+/// ```
+/// use memcomparable::{Serializer, TeeBuf};
+/// use serde::Serialize;
+///
+/// let mut ser = Serializer::new(TeeBuf::new(vec![], vec![]));
+/// "hello".serialize(&mut ser).unwrap();
+/// let (a, b) = ser.into_inner().into_inner();
+/// assert_eq!(a, b);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TeeBuf<A: BufMut, B: BufMut> {
+    a: A,
+    b: B,
+    // bytes written through `chunk_mut` are staged here until `advance_mut` copies them to both
+    staging: [u8; STAGING_SIZE],
+}
+
+impl<A: BufMut, B: BufMut> TeeBuf<A, B> {
+    /// Create a `TeeBuf` writing to `a` and `b`.
+    pub fn new(a: A, b: B) -> Self {
+        TeeBuf {
+            a,
+            b,
+            staging: [0; STAGING_SIZE],
+        }
+    }
+
+    /// Unwrap the two inner buffers.
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+unsafe impl<A: BufMut, B: BufMut> BufMut for TeeBuf<A, B> {
+    fn remaining_mut(&self) -> usize {
+        self.a.remaining_mut().min(self.b.remaining_mut())
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        let staged = &self.staging[..cnt];
+        self.a.put_slice(staged);
+        self.b.put_slice(staged);
+    }
+
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        let len = STAGING_SIZE.min(self.remaining_mut());
+        UninitSlice::new(&mut self.staging[..len])
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        self.a.put_slice(src);
+        self.b.put_slice(src);
+    }
+
+    fn put_bytes(&mut self, val: u8, cnt: usize) {
+        self.a.put_bytes(val, cnt);
+        self.b.put_bytes(val, cnt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+    use crate::{to_vec, Serializer};
+
+    #[test]
+    fn test_tee() {
+        #[derive(Serialize)]
+        struct Row {
+            id: u64,
+            name: String,
+            score: Option<f64>,
+        }
+
+        let row = Row {
+            id: 42,
+            name: "a somewhat long name".to_string(),
+            score: Some(0.5),
+        };
+        let mut ser = Serializer::new(TeeBuf::new(vec![], vec![]));
+        row.serialize(&mut ser).unwrap();
+        let (a, b) = ser.into_inner().into_inner();
+        let expected = to_vec(&row).unwrap();
+        assert_eq!(a, expected);
+        assert_eq!(b, expected);
+    }
+
+    #[test]
+    fn test_chunk_mut() {
+        // the smaller buffer limits the chunk
+        let mut array = [0u8; 4];
+        let mut tee = TeeBuf::new(vec![], array.as_mut_slice());
+        assert_eq!(tee.remaining_mut(), 4);
+        let chunk = tee.chunk_mut();
+        assert_eq!(chunk.len(), 4);
+        chunk[..3].copy_from_slice(&[1, 2, 3]);
+        unsafe { tee.advance_mut(3) };
+        let (a, _) = tee.into_inner();
+        assert_eq!(a, [1, 2, 3]);
+        assert_eq!(array, [1, 2, 3, 0]);
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers for testing that a type's encoding preserves its order.
+
+use std::fmt::Debug;
+
+use serde::Serialize;
+
+use crate::{common_prefix_len, to_vec, to_vec_desc};
+
+/// Assert that the encodings of `a` and `b` compare the same way as the values, in both
+/// ascending and descending order.
+///
+/// # Panics
+///
+/// Panics if either value fails to serialize, or if the orders differ. The message shows both
+/// values, their encodings and the first byte at which the encodings differ.
+///
+/// # Example
+/// ```
+/// use memcomparable::test_util::assert_order;
+///
+/// assert_order(&(1u8, "b"), &(2u8, "a"));
+/// assert_order(&-1i32, &1i32);
+/// ```
+#[track_caller]
+pub fn assert_order<T>(a: &T, b: &T)
+where
+    T: Serialize + Ord + Debug,
+{
+    let expected = a.cmp(b);
+    for desc in [false, true] {
+        let encode = |v: &T| {
+            if desc {
+                to_vec_desc(v).expect("failed to serialize")
+            } else {
+                to_vec(v).expect("failed to serialize")
+            }
+        };
+        let (ea, eb) = (encode(a), encode(b));
+        let actual = if desc {
+            ea.cmp(&eb).reverse()
+        } else {
+            ea.cmp(&eb)
+        };
+        assert_eq!(
+            actual,
+            expected,
+            "{} order mismatch\n  a: {a:?}\n     {ea:02x?}\n  b: {b:?}\n     {eb:02x?}\n  encodings first differ at byte {}",
+            if desc { "descending" } else { "ascending" },
+            common_prefix_len(&ea, &eb),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assert_order() {
+        let ints = [i64::MIN, -1, 0, 1, i64::MAX];
+        for a in &ints {
+            for b in &ints {
+                assert_order(a, b);
+            }
+        }
+        let strings = ["", "a", "a\0", "ab", "b", "the quick brown fox"];
+        for a in &strings {
+            for b in &strings {
+                assert_order(a, b);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "ascending order mismatch")]
+    fn test_assert_order_mismatch() {
+        // a type whose `Ord` disagrees with its encoding
+        #[derive(Debug, PartialEq, Eq, Serialize)]
+        struct Reversed(u8);
+
+        impl PartialOrd for Reversed {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Reversed {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                other.0.cmp(&self.0)
+            }
+        }
+
+        assert_order(&Reversed(1), &Reversed(2));
+    }
+}
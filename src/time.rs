@@ -0,0 +1,103 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversions between `SystemTime` and epoch timestamps.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const NANOS_PER_MILLI: u128 = 1_000_000;
+const NANOS_PER_MICRO: u128 = 1_000;
+
+/// Return the milliseconds since the Unix epoch, or `None` if out of the range of `i64`.
+///
+/// Times before the epoch are negative. The result is rounded down, so the order of times is
+/// preserved.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let time = UNIX_EPOCH - Duration::from_micros(1500);
+/// assert_eq!(memcomparable::system_time_to_millis(time), Some(-2));
+/// ```
+pub fn system_time_to_millis(time: SystemTime) -> Option<i64> {
+    to_ticks(time, NANOS_PER_MILLI)
+}
+
+/// Return the `SystemTime` of milliseconds since the Unix epoch, or `None` if not representable.
+pub fn system_time_from_millis(millis: i64) -> Option<SystemTime> {
+    from_duration(millis, Duration::from_millis(millis.unsigned_abs()))
+}
+
+/// Return the microseconds since the Unix epoch, or `None` if out of the range of `i64`.
+///
+/// Times before the epoch are negative. The result is rounded down, so the order of times is
+/// preserved.
+pub fn system_time_to_micros(time: SystemTime) -> Option<i64> {
+    to_ticks(time, NANOS_PER_MICRO)
+}
+
+/// Return the `SystemTime` of microseconds since the Unix epoch, or `None` if not representable.
+pub fn system_time_from_micros(micros: i64) -> Option<SystemTime> {
+    from_duration(micros, Duration::from_micros(micros.unsigned_abs()))
+}
+
+fn to_ticks(time: SystemTime, nanos_per_tick: u128) -> Option<i64> {
+    let ticks = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => (since.as_nanos() / nanos_per_tick) as i128,
+        Err(e) => -(e.duration().as_nanos().div_ceil(nanos_per_tick) as i128),
+    };
+    i64::try_from(ticks).ok()
+}
+
+fn from_duration(ticks: i64, duration: Duration) -> Option<SystemTime> {
+    if ticks >= 0 {
+        UNIX_EPOCH.checked_add(duration)
+    } else {
+        UNIX_EPOCH.checked_sub(duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_time() {
+        let cases = [
+            (UNIX_EPOCH, 0),
+            (UNIX_EPOCH + Duration::from_nanos(1), 0),
+            (UNIX_EPOCH + Duration::from_micros(1999), 1),
+            (UNIX_EPOCH - Duration::from_nanos(1), -1),
+            (UNIX_EPOCH - Duration::from_millis(1), -1),
+            (UNIX_EPOCH - Duration::from_micros(1001), -2),
+        ];
+        for (time, millis) in cases {
+            assert_eq!(system_time_to_millis(time), Some(millis), "{time:?}");
+        }
+        assert_eq!(
+            system_time_to_micros(UNIX_EPOCH - Duration::from_nanos(1500)),
+            Some(-2)
+        );
+
+        for millis in [i64::MIN / 1000, -1, 0, 1, 1_700_000_000_000] {
+            let time = system_time_from_millis(millis).unwrap();
+            assert_eq!(system_time_to_millis(time), Some(millis));
+        }
+        for micros in [-1_000_001, -1, 0, 1, 1_700_000_000_000_000] {
+            let time = system_time_from_micros(micros).unwrap();
+            assert_eq!(system_time_to_micros(time), Some(micros));
+        }
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright 2022 Singularity Data
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Measuring the width of fixed-width encodings without serializing them.
+
+use serde::ser::{self, Error as _, Impossible, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Return the encoded width of `value`, which must be the same for all values of its type.
+///
+/// Returns an error for strings, byte arrays, sequences, maps, options and enums, whose width
+/// depends on the value.
+pub(crate) fn fixed_width<T: ?Sized + Serialize>(value: &T) -> Result<usize> {
+    value.serialize(Width)
+}
+
+/// A serializer that returns the width of the encoding of a fixed-width value.
+struct Width;
+
+/// The sum of the widths of the fields of a compound value.
+struct Sum(usize);
+
+impl Sum {
+    fn field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.0 += value.serialize(Width)?;
+        Ok(())
+    }
+}
+
+/// Return the width of a primitive type.
+macro_rules! fixed {
+    ($($method:ident($ty:ty) = $width:expr;)*) => {
+        $(
+            fn $method(self, _: $ty) -> Result<usize> {
+                Ok($width)
+            }
+        )*
+    };
+}
+
+/// Reject a type whose width depends on the value.
+macro_rules! variable {
+    ($($method:ident($($arg:ty),*) -> $ret:ty = $name:literal;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret> {
+                Err(Error::custom(concat!($name, " has no fixed width")))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for Width {
+    type Ok = usize;
+    type Error = Error;
+    type SerializeSeq = Impossible<usize, Error>;
+    type SerializeTuple = Sum;
+    type SerializeTupleStruct = Sum;
+    type SerializeTupleVariant = Impossible<usize, Error>;
+    type SerializeMap = Impossible<usize, Error>;
+    type SerializeStruct = Sum;
+    type SerializeStructVariant = Impossible<usize, Error>;
+
+    fixed! {
+        serialize_bool(bool) = 1;
+        serialize_i8(i8) = 1;
+        serialize_i16(i16) = 2;
+        serialize_i32(i32) = 4;
+        serialize_i64(i64) = 8;
+        serialize_i128(i128) = 16;
+        serialize_u8(u8) = 1;
+        serialize_u16(u16) = 2;
+        serialize_u32(u32) = 4;
+        serialize_u64(u64) = 8;
+        serialize_u128(u128) = 16;
+        serialize_f32(f32) = 4;
+        serialize_f64(f64) = 8;
+        serialize_char(char) = 4;
+        serialize_unit_struct(&'static str) = 0;
+    }
+
+    variable! {
+        serialize_str(&str) -> usize = "str";
+        serialize_bytes(&[u8]) -> usize = "bytes";
+        serialize_none() -> usize = "option";
+        serialize_unit_variant(&'static str, u32, &'static str) -> usize = "enum";
+        serialize_seq(Option<usize>) -> Self::SerializeSeq = "seq";
+        serialize_tuple_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeTupleVariant = "enum";
+        serialize_map(Option<usize>) -> Self::SerializeMap = "map";
+        serialize_struct_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeStructVariant = "enum";
+    }
+
+    fn serialize_unit(self) -> Result<usize> {
+        Ok(0)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<usize> {
+        Err(Error::custom("option has no fixed width"))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<usize> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<usize> {
+        Err(Error::custom("enum has no fixed width"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Sum> {
+        Ok(Sum(0))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Sum> {
+        Ok(Sum(0))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Sum> {
+        Ok(Sum(0))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        // the default of `Serializer`, e.g. `Ipv4Addr` is 4 octets instead of a string
+        false
+    }
+}
+
+impl ser::SerializeTuple for Sum {
+    type Ok = usize;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<usize> {
+        Ok(self.0)
+    }
+}
+
+impl ser::SerializeTupleStruct for Sum {
+    type Ok = usize;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<usize> {
+        Ok(self.0)
+    }
+}
+
+impl ser::SerializeStruct for Sum {
+    type Ok = usize;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<usize> {
+        Ok(self.0)
+    }
+}